@@ -13,7 +13,12 @@ pub enum Binop {
     Eq,
     Neq,
     And,
-    Or
+    Or,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
 }
 ////////////////////////////////////////////////////////////////////////////////
 
@@ -36,6 +41,11 @@ impl Display for Binop {
             Neq => write!(f, "!="),
             And => write!(f, "&&"),
             Or => write!(f, "||"),
+            BitAnd => write!(f, "&"),
+            BitOr => write!(f, "|"),
+            BitXor => write!(f, "^"),
+            Shl => write!(f, "<<"),
+            Shr => write!(f, ">>"),
         }
     }
 }
@@ -60,6 +70,11 @@ impl From<ParseBinop> for Result<Binop, ParseError> {
             ParseBinop::Neq => Ok(Binop::Neq),
             ParseBinop::And => Ok(Binop::And),
             ParseBinop::Or => Ok(Binop::Or),
+            ParseBinop::BitAnd => Ok(Binop::BitAnd),
+            ParseBinop::BitOr => Ok(Binop::BitOr),
+            ParseBinop::BitXor => Ok(Binop::BitXor),
+            ParseBinop::Shl => Ok(Binop::Shl),
+            ParseBinop::Shr => Ok(Binop::Shr),
         }
     }
 }
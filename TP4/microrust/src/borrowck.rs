@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+
+use crate::binop::Binop;
+use crate::error::EvalError;
+use crate::expression::Expression;
+use crate::identifier::Identifier;
+use crate::instruction::Instruction;
+use crate::parsing::pattern::Pattern;
+use crate::parsing::ptr_kind::PtrKind;
+
+// emprunt de la variable `id` déclarée à la profondeur `depth`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Loan {
+    depth: usize,
+    id: Identifier,
+    mutable: bool,
+}
+
+// emprunts détenus par une variable : ils vivent tant qu'elle est en portée
+// `owned` : la valeur n'est pas `Copy`, la lire en position de move la déplace
+#[derive(Debug, Clone)]
+struct Binding {
+    mutable: bool,
+    owned: bool,
+    loans: Vec<Loan>,
+}
+
+// pile de portées parallèle à la NameSpaceStack, vérifiée avant l'exécution
+#[derive(Debug, Clone)]
+pub struct BorrowEnv {
+    scopes: Vec<HashMap<Identifier, Binding>>,
+}
+
+impl BorrowEnv {
+    pub fn new() -> Self {
+        BorrowEnv { scopes: vec![HashMap::new()] }
+    }
+
+    fn push(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn pop(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn depth(&self) -> usize {
+        self.scopes.len() - 1
+    }
+
+    fn declare(&mut self, id: Identifier, mutable: bool, owned: bool, loans: Vec<Loan>) {
+        self.scopes.last_mut().unwrap().insert(id, Binding { mutable, owned, loans });
+    }
+
+    fn find(&self, id: &Identifier) -> Option<(usize, &Binding)> {
+        self.scopes.iter().enumerate().rev().find_map(|(depth, scope)| scope.get(id).map(|b| (depth, b)))
+    }
+
+    fn find_mut(&mut self, id: &Identifier) -> Option<&mut Binding> {
+        self.scopes.iter_mut().rev().find_map(|scope| scope.get_mut(id))
+    }
+
+    // emprunts encore vivants de la variable `id` déclarée à la profondeur `depth`
+    fn loans_on<'a>(&'a self, depth: usize, id: &'a Identifier) -> impl Iterator<Item = &'a Loan> + 'a {
+        self.scopes.iter()
+            .flat_map(|scope| scope.values())
+            .flat_map(|binding| binding.loans.iter())
+            .filter(move |loan| loan.depth == depth && loan.id == *id)
+    }
+
+    fn is_borrowed(&self, id: &Identifier) -> bool {
+        match self.find(id) {
+            Some((depth, _)) => self.loans_on(depth, id).next().is_some(),
+            None => false,
+        }
+    }
+}
+
+
+fn declare_pattern(env: &mut BorrowEnv, pattern: &Pattern, owned: bool, loans: &[Loan]) {
+    match pattern {
+        Pattern::Identifier{id, mutable} => env.declare(id.clone(), *mutable, owned, loans.to_vec()),
+        Pattern::Tuple(patterns) => for pattern in patterns {
+            declare_pattern(env, pattern, owned, loans);
+        },
+    }
+}
+
+
+impl Expression {
+    // variable désignée par une expression gauche (`x`, `*x`, `x[i]`, `x.i`...)
+    fn root(&self) -> Option<&Identifier> {
+        match self {
+            Expression::Identifier(id) => Some(id),
+            Expression::Deref(e) | Expression::Index(e, _) | Expression::Field(e, _) => e.root(),
+            _ => None,
+        }
+    }
+
+    // la valeur de l'expression est possédée : pointeurs `Box` et `Rc`,
+    // String, Vec, et tableaux ou tuples qui en contiennent
+    fn owns(&self, env: &BorrowEnv) -> bool {
+        match self {
+            Expression::Identifier(id) => env.find(id).is_some_and(|(_, b)| b.owned),
+            Expression::NewPtr(kind, _) => *kind != PtrKind::RawPtr,
+            Expression::RcClone(_) | Expression::StringFrom(_) | Expression::VecFrom(_) => true,
+            Expression::Array(es) | Expression::Tuple(es) => es.iter().any(|e| e.owns(env)),
+            // `s + t` est une String si `s` en est une
+            Expression::Repeat(e, _) | Expression::BinOp(e, Binop::Add, _)
+            | Expression::Index(e, _) | Expression::Field(e, _) => e.owns(env),
+            Expression::Conditional{cond_true, cond_false, ..} => cond_true.owns(env) || cond_false.owns(env),
+            _ => false,
+        }
+    }
+
+    fn borrow(&self, place: &Expression, mutable: bool, env: &BorrowEnv) -> Result<Vec<Loan>, EvalError> {
+        let id = match place.root() {
+            Some(id) => id,
+            None => return Ok(vec![]),
+        };
+        let (depth, binding) = match env.find(id) {
+            Some(found) => found,
+            None => return Ok(vec![]),
+        };
+        if mutable && matches!(place, Expression::Identifier(_)) && !binding.mutable {
+            return Err(EvalError::NotMutable(Some(self.clone())));
+        }
+        let mut loans = env.loans_on(depth, id);
+        if mutable && loans.next().is_some() {
+            return Err(EvalError::CannotBorrowMut(place.clone()));
+        }
+        if !mutable && loans.any(|loan| loan.mutable) {
+            return Err(EvalError::CannotBorrowShared(place.clone()));
+        }
+        // emprunter une référence prolonge aussi les emprunts qu'elle détient
+        let mut loans = place.loans(env)?;
+        loans.push(Loan { depth, id: id.clone(), mutable });
+        Ok(loans)
+    }
+
+    // emprunts détenus par la valeur de l'expression
+    fn loans(&self, env: &BorrowEnv) -> Result<Vec<Loan>, EvalError> {
+        match self {
+            Expression::Const(_) | Expression::RawAlloc => Ok(vec![]),
+            Expression::Identifier(id) => Ok(env.find(id).map(|(_, b)| b.loans.clone()).unwrap_or_default()),
+            // `s + t` déplace la String `s`
+            Expression::BinOp(e1, op, e2) => {
+                if *op == Binop::Add {
+                    Instruction::check_move(e1, env)?;
+                }
+                e1.loans(env)?;
+                e2.loans(env)?;
+                Ok(vec![])
+            },
+            Expression::Conditional{cond, cond_true, cond_false} => {
+                cond.loans(env)?;
+                let mut loans = cond_true.loans(env)?;
+                loans.extend(cond_false.loans(env)?);
+                Ok(loans)
+            },
+            Expression::AmpersAnd(e) => self.borrow(e, false, env),
+            Expression::AmpersAndMut(e) => self.borrow(e, true, env),
+            Expression::NewPtr(_, e) => {
+                Instruction::check_move(e, env)?;
+                e.loans(env)
+            },
+            Expression::RcClone(e) | Expression::VecFrom(e) | Expression::Field(e, _) => e.loans(env),
+            // un tableau ou un tuple détient les emprunts de tous ses éléments
+            Expression::Array(es) | Expression::Tuple(es) => {
+                let mut loans = vec![];
+                for e in es {
+                    Instruction::check_move(e, env)?;
+                    loans.extend(e.loans(env)?);
+                }
+                Ok(loans)
+            },
+            Expression::Repeat(e, i) | Expression::Index(e, i) => {
+                i.loans(env)?;
+                e.loans(env)
+            },
+            // `push` et `pop` modifient le Vec, qui ne doit pas être emprunté
+            Expression::Push(e, x) => {
+                Instruction::check_move(x, env)?;
+                x.loans(env)?;
+                Instruction::check_not_borrowed(e, env)?;
+                Ok(vec![])
+            },
+            Expression::Pop(e) => {
+                Instruction::check_not_borrowed(e, env)?;
+                e.loans(env)
+            },
+            Expression::Deref(e) | Expression::UnOp(_, e) | Expression::Cast(e, _)
+            | Expression::Len(e) | Expression::StringFrom(e) => {
+                e.loans(env)?;
+                Ok(vec![])
+            },
+        }
+    }
+}
+
+
+impl Instruction {
+    // une place empruntée ne peut être ni écrasée ni libérée
+    fn check_not_borrowed(place: &Expression, env: &BorrowEnv) -> Result<(), EvalError> {
+        match place.root() {
+            Some(id) if env.is_borrowed(id) => Err(EvalError::BorrowedValue(place.clone())),
+            _ => Ok(()),
+        }
+    }
+
+    // une place possédée lue en position de move est déplacée
+    fn check_move(expr: &Expression, env: &BorrowEnv) -> Result<(), EvalError> {
+        match expr {
+            Expression::Conditional{cond_true, cond_false, ..} => {
+                Self::check_move(cond_true, env)?;
+                Self::check_move(cond_false, env)
+            },
+            _ if expr.root().is_some() && expr.owns(env) => Self::check_not_borrowed(expr, env),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn borrowck(&self, env: &mut BorrowEnv) -> Result<Vec<Loan>, EvalError> {
+        match self {
+            Instruction::Print{args, ..} => {
+                for arg in args {
+                    arg.loans(env)?;
+                }
+                Ok(vec![])
+            },
+            Instruction::Expr(e) => e.loans(env),
+            // les variables d'un motif détiennent toutes les emprunts de la valeur
+            Instruction::Let{pattern, expr, ..} => {
+                Self::check_move(expr, env)?;
+                let loans = expr.loans(env)?;
+                declare_pattern(env, pattern, expr.owns(env), &loans);
+                Ok(loans)
+            },
+            Instruction::Block(instrs) => {
+                env.push();
+                let mut loans = vec![];
+                for instr in instrs {
+                    loans = instr.borrowck(env).inspect_err(|_| env.pop())?;
+                }
+                let depth = env.depth();
+                env.pop();
+                match loans.iter().find(|loan| loan.depth == depth) {
+                    Some(loan) => Err(EvalError::DoesNotLiveLongEnough(loan.id.clone())),
+                    None => Ok(loans),
+                }
+            },
+            Instruction::WriteAt(place, expr) => {
+                Self::check_move(expr, env)?;
+                let loans = expr.loans(env)?;
+                Self::check_not_borrowed(place, env)?;
+                if let Some(id) = place.root() {
+                    if let Some((depth, _)) = env.find(id) {
+                        if let Some(loan) = loans.iter().find(|loan| loan.depth > depth) {
+                            return Err(EvalError::DoesNotLiveLongEnough(loan.id.clone()));
+                        }
+                    }
+                    let overwrite = matches!(place, Expression::Identifier(_));
+                    if let Some(binding) = env.find_mut(id) {
+                        if overwrite {
+                            binding.loans = loans;
+                        } else {
+                            binding.loans.extend(loans);
+                        }
+                    }
+                }
+                Ok(vec![])
+            },
+            Instruction::Free(e) | Instruction::Drop(e) => {
+                Self::check_not_borrowed(e, env)?;
+                if let (Expression::Identifier(id), Instruction::Drop(_)) = (e, self) {
+                    if let Some(binding) = env.find_mut(id) {
+                        binding.loans.clear();
+                    }
+                }
+                Ok(vec![])
+            },
+        }
+    }
+}
@@ -2,13 +2,16 @@ use std::fmt::{self, Display};
 
 use crate::expression::Expression;
 use crate::identifier::Identifier;
+use crate::parsing::pattern::Pattern;
 use crate::r#type::Type;
 
 use crate::parser::ParseError;
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
     ParseError(ParseError),
+    BorrowError(EvalError),
     EvalError(EvalError),
 }
 
@@ -16,10 +19,30 @@ pub enum Error {
 #[allow(dead_code)]
 pub enum EvalError {
     DivisionByZero(Expression),
+    Overflow(Expression),
     Undefined(Identifier),
     AlreadyDefined(Identifier),
     NotMutable(Option<Expression>),
     TypeMismatch{expression: Expression, expected: Type, found: Type},
+    NonAllocatedCell(Option<Expression>),
+    NonInitializedValue(Option<Expression>),
+    UseAfterFree(Option<Expression>),
+    DoubleFree(Option<Expression>),
+    DanglingPointer(Option<Expression>),
+    MovedValue(Option<Expression>),
+    CannotFreeOwnedValue(Option<Expression>),
+    NotReferenceCounted(Expression),
+    BorrowedValue(Expression),
+    CannotBorrowMut(Expression),
+    CannotBorrowShared(Expression),
+    DoesNotLiveLongEnough(Identifier),
+    IndexOutOfBounds{expression: Option<Expression>, index: i128, len: usize},
+    CannotMoveOutOfIndex(Option<Expression>),
+    EmptyVec(Expression),
+    NotCopy(Expression),
+    NoField{expression: Expression, found: Type},
+    CannotIndex{expression: Expression, found: Type},
+    PatternMismatch{pattern: Pattern, found: Type},
 }
 
 impl From<ParseError> for Error {
@@ -39,10 +62,32 @@ impl Display for EvalError {
         use EvalError::*;
         match self {
             DivisionByZero(e) => write!(f, "Division by zero, `{}` evaluates to 0", e),
+            Overflow(e) => write!(f, "Arithmetic overflow in `{}`", e),
             Undefined(id) => write!(f, "Undefined identifier: `{}`", id),
             AlreadyDefined(id) => write!(f, "Identifier `{}` already defined.", id),
             NotMutable(e) => write!(f, "Cell {}is not mutable.", e.as_ref().map(|e| format!("at `{}` ", e)).unwrap_or("".to_string())),
-            TypeMismatch => write!(f, "Type mismatch"),
+            TypeMismatch { expression, expected, found } => {
+                write!(f, "Type mismatch in expression `{}`. Expected: {}. Found: {}", expression, expected, found)
+            },
+            NonAllocatedCell(e) => write!(f, "Cell {}is not allocated.", e.as_ref().map(|e| format!("at `{}` ", e)).unwrap_or("".to_string())),
+            NonInitializedValue(e) => write!(f, "Value {}is not initialized.", e.as_ref().map(|e| format!("in `{}` ", e)).unwrap_or("".to_string())),
+            UseAfterFree(e) => write!(f, "{}use after free.", e.as_ref().map(|e| format!("`{}` is a ", e)).unwrap_or("".to_string())),
+            DoubleFree(e) => write!(f, "{}double free.", e.as_ref().map(|e| format!("`{}` is a ", e)).unwrap_or("".to_string())),
+            DanglingPointer(e) => write!(f, "{}dangling pointer to a popped stack frame.", e.as_ref().map(|e| format!("`{}` is a ", e)).unwrap_or("".to_string())),
+            MovedValue(e) => write!(f, "{} has been moved", e.as_ref().map(|e| format!("`{}`", e)).unwrap_or("value".to_string())),
+            CannotFreeOwnedValue(e) => write!(f, "cannot free {}, owned value", e.as_ref().map(|e| format!("`{}`", e)).unwrap_or("this value".to_string())),
+            NotReferenceCounted(e) => write!(f, "`{}` is not a reference counted pointer", e),
+            BorrowedValue(e) => write!(f, "cannot assign to or move `{}`, it is borrowed", e),
+            CannotBorrowMut(e) => write!(f, "cannot borrow `{}` as mutable, it is already borrowed", e),
+            CannotBorrowShared(e) => write!(f, "cannot borrow `{}` as immutable, it is already borrowed as mutable", e),
+            DoesNotLiveLongEnough(id) => write!(f, "`{}` does not live long enough", id),
+            IndexOutOfBounds { expression, index, len } => write!(f, "index out of bounds: the len is {} but the index is {}{}", len, index, expression.as_ref().map(|e| format!(" in `{}`", e)).unwrap_or("".to_string())),
+            CannotMoveOutOfIndex(e) => write!(f, "cannot move out of {}, an indexed element", e.as_ref().map(|e| format!("`{}`", e)).unwrap_or("this value".to_string())),
+            EmptyVec(e) => write!(f, "cannot pop from `{}`, the Vec is empty", e),
+            NotCopy(e) => write!(f, "`{}` cannot be copied to fill an array", e),
+            NoField { expression, found } => write!(f, "No such field in `{}` on a value of type {}", expression, found),
+            CannotIndex { expression, found } => write!(f, "Cannot index into `{}`, a value of type {}", expression, found),
+            PatternMismatch { pattern, found } => write!(f, "Pattern `{}` does not match a value of type {}", pattern, found),
         }
     }
 }
@@ -52,7 +97,26 @@ impl Display for Error {
         use Error::*;
         match self {
             ParseError(e) => write!(f, "Parse Error: {}", e),
+            BorrowError(e) => write!(f, "Borrow Error: {}", e),
             EvalError(e) => write!(f, "Evaluation Error: {}", e),
         }
     }
-}
\ No newline at end of file
+}
+
+impl EvalError {
+    pub fn with_expression_info(&self, e: Expression) -> Self {
+        use EvalError::*;
+        match self {
+            NotMutable(None) => NotMutable(Some(e)),
+            NonAllocatedCell(None) => NonAllocatedCell(Some(e)),
+            NonInitializedValue(None) => NonInitializedValue(Some(e)),
+            UseAfterFree(None) => UseAfterFree(Some(e)),
+            DoubleFree(None) => DoubleFree(Some(e)),
+            DanglingPointer(None) => DanglingPointer(Some(e)),
+            MovedValue(None) => MovedValue(Some(e)),
+            IndexOutOfBounds{expression: None, index, len} => IndexOutOfBounds{expression: Some(e), index: *index, len: *len},
+            CannotMoveOutOfIndex(None) => CannotMoveOutOfIndex(Some(e)),
+            _ => self.clone(),
+        }
+    }
+}
@@ -1,26 +1,166 @@
 use crate::error::EvalError;
 use crate::expression::Expression;
 use crate::binop::Binop;
-use crate::memory::Memory;
 use crate::namespacestack::NameSpaceStack;
-use crate::parsing::parsedvalue::ParsedValue;
+use crate::parsing::ptr_kind::PtrKind;
+use crate::unop::Unop;
 use crate::{instruction::Instruction, namespace::NameSpace};
 use crate::identifier::Identifier;
 use crate::value::Value;
 use crate::memory::Address;
 use crate::r#type::Type;
+use std::rc::Rc;
+use crate::parsing::parsedvalue::ParsedValue;
+use crate::parsing::format::{Align, Piece, Spec};
+use crate::parsing::pattern::Pattern;
+
+
+// comme en Rust : l'arithmétique est vérifiée, sauf en mode `--wrapping`
+// où un dépassement revient à l'autre extrémité du type ; un décalage
+// d'au moins la taille du type déborde aussi, mais les bits sortis sont
+// perdus sans erreur
+fn arithmetic(op: Binop, v1: i128, v2: i128, ty: &Type, wrapping: bool) -> Option<i128> {
+    let shift = |v2: i128| if wrapping {
+        Some(v2.rem_euclid(ty.bits() as i128) as u32)
+    } else {
+        u32::try_from(v2).ok().filter(|shift| *shift < ty.bits())
+    };
+    let result = match op {
+        Binop::Add => v1.checked_add(v2),
+        Binop::Sub => v1.checked_sub(v2),
+        Binop::Mul if wrapping => Some(v1.wrapping_mul(v2)),
+        Binop::Mul => v1.checked_mul(v2),
+        Binop::Div => v1.checked_div(v2),
+        // `MIN % -1` déborde, comme `MIN / -1`
+        Binop::Mod => v1.checked_div(v2).filter(|q| wrapping || ty.contains(*q)).map(|_| v1 % v2),
+        Binop::Shl => return shift(v2).map(|shift| ty.wrap(v1 << shift)),
+        Binop::Shr => return shift(v2).map(|shift| v1 >> shift),
+        _ => unreachable!("{} is not an arithmetic operator", op),
+    }?;
+    if wrapping {
+        Some(ty.wrap(result))
+    } else {
+        Some(result).filter(|result| ty.contains(*result))
+    }
+}
+
+// une valeur stockée a un type fixé : un entier sans suffixe prend le
+// type entier attendu, `isize` par défaut, y compris dans un tuple ou un
+// tableau ; les éléments d'un tableau littéral n'attendent que leur type commun
+fn with_int_type(value: Value, expected: Option<Type>, e: &Expression) -> Result<Value, EvalError> {
+    match value {
+        Value::Integer(i, Type::IntLiteral) => {
+            let ty = expected.filter(Type::is_int).unwrap_or(Type::Int);
+            if ty.contains(i) {
+                Ok(Value::Integer(i, ty))
+            } else {
+                Err(EvalError::Overflow(e.clone()))
+            }
+        },
+        Value::Tuple(values) => {
+            let expected = match expected {
+                Some(Type::Tuple(types)) if types.len() == values.len() => types.into_iter().map(Some).collect(),
+                _ => vec![None; values.len()],
+            };
+            let values = values.into_iter().zip(expected).map(|(value, ty)| with_int_type(value, ty, e));
+            Ok(Value::Tuple(values.collect::<Result<_, _>>()?))
+        },
+        Value::Array(values) => {
+            let expected = match expected {
+                Some(Type::Array(Some(ty), _)) => Some(*ty),
+                _ => None,
+            };
+            let values = values.into_iter().map(|value| with_int_type(value, expected.clone(), e));
+            Ok(Value::Array(values.collect::<Result<_, _>>()?))
+        },
+        value => Ok(value),
+    }
+}
+
+// le type annoté d'un `let` est donné, avant l'évaluation, aux entiers sans
+// suffixe sous les opérateurs arithmétiques : `let x: u8 = !0` vaut 255
+fn with_literal_type(e: &Expression, ty: Type) -> Expression {
+    match (e, ty) {
+        (Expression::Const(ParsedValue::Integer(i, Type::IntLiteral)), ty) if ty.is_int() =>
+            Expression::Const(ParsedValue::Integer(*i, ty)),
+        (Expression::UnOp(op, e), ty) if ty.is_int() => Expression::UnOp(*op, Box::new(with_literal_type(e, ty))),
+        (Expression::BinOp(e1, op @ (Binop::Add | Binop::Sub | Binop::Mul | Binop::Div | Binop::Mod
+            | Binop::BitAnd | Binop::BitOr | Binop::BitXor | Binop::Shl | Binop::Shr), e2), ty) if ty.is_int() => {
+            // le membre droit d'un décalage garde son propre type
+            let e2 = match op {
+                Binop::Shl | Binop::Shr => *e2.clone(),
+                _ => with_literal_type(e2, ty.clone()),
+            };
+            Expression::BinOp(Box::new(with_literal_type(e1, ty)), *op, Box::new(e2))
+        },
+        (Expression::Tuple(es), Type::Tuple(types)) if es.len() == types.len() =>
+            Expression::Tuple(es.iter().zip(types).map(|(e, ty)| with_literal_type(e, ty)).collect()),
+        (Expression::Array(es), Type::Array(Some(ty), _)) => Expression::Array(es.iter().map(|e| with_literal_type(e, (*ty).clone())).collect()),
+        (e, _) => e.clone(),
+    }
+}
+
+// variables liées par `pattern` et parties de `value` qui leur reviennent
+fn destructure<'a>(pattern: &'a Pattern, value: Value, bindings: &mut Vec<(&'a Identifier, bool, Value)>) -> Result<(), EvalError> {
+    match (pattern, value) {
+        (Pattern::Identifier{id, mutable}, value) => bindings.push((id, *mutable, value)),
+        (Pattern::Tuple(patterns), Value::Tuple(values)) if patterns.len() == values.len() => {
+            for (pattern, value) in patterns.iter().zip(values) {
+                destructure(pattern, value, bindings)?;
+            }
+        },
+        (pattern, value) => return Err(EvalError::PatternMismatch{pattern: pattern.clone(), found: Type::from(&value)}),
+    }
+    Ok(())
+}
 
+// égalité de deux valeurs comparables, dont les String et les Vec ont été
+// remplacés par leur contenu : les entiers par leur valeur, les tableaux et
+// les tuples élément par élément
+fn equal(v1: &Value, v2: &Value) -> bool {
+    match (v1, v2) {
+        (Value::Integer(i1, _), Value::Integer(i2, _)) => i1 == i2,
+        (Value::Array(values1) | Value::Tuple(values1), Value::Array(values2) | Value::Tuple(values2)) =>
+            values1.len() == values2.len() && values1.iter().zip(values2).all(|(v1, v2)| equal(v1, v2)),
+        _ => v1 == v2,
+    }
+}
+
+// `{}` affiche une valeur comme `Display`, `{:?}` comme `Value::debug` ; la précision tronque une chaîne, et les
+// nombres sont alignés à droite par défaut
+// les String et les Vec de `value` ont été remplacés par leur contenu
+fn render(value: &Value, spec: &Spec) -> String {
+    let text = match (value, spec.debug) {
+        (_, true) => value.debug(),
+        (Value::Str(s), false) if spec.precision.is_some() =>
+            s.chars().take(spec.precision.unwrap()).collect(),
+        _ => value.to_string(),
+    };
+    let padding = spec.width.unwrap_or(0).saturating_sub(text.chars().count());
+    if spec.zero && matches!(value, Value::Integer(..)) {
+        let (sign, digits) = text.split_at(if text.starts_with('-') { 1 } else { 0 });
+        return format!("{}{}{}", sign, "0".repeat(padding), digits);
+    }
+    let align = spec.align.unwrap_or(if matches!(value, Value::Integer(..)) { Align::Right } else { Align::Left });
+    let (left, right) = match align {
+        Align::Left => (0, padding),
+        Align::Right => (padding, 0),
+        Align::Center => (padding / 2, padding - padding / 2),
+    };
+    let fill = spec.fill.to_string();
+    format!("{}{}{}", fill.repeat(left), text, fill.repeat(right))
+}
 
 impl Expression {
 
-    fn eval_and_cast_to_int(&self, nss: &mut NameSpaceStack) -> Result<isize, EvalError> {
+    fn cast_to_int(&self, v: &Value) -> Result<(i128, Type), EvalError> {
+        v.to_int()
+            .map_err(|found| EvalError::TypeMismatch{expression: self.clone(), expected: Type::Int, found})
+    }
+
+    fn eval_and_cast_to_int(&self, nss: &mut NameSpaceStack) -> Result<(i128, Type), EvalError> {
         let v = self.eval(nss)?;
-        let cloned_v = v.clone();
-        cloned_v.to_int()
-            .map_err(|_| EvalError::TypeMismatch{
-                expression: self.clone(), 
-                expected: Type::Int, 
-                found: Type::from(&v)})
+        self.cast_to_int(&v)
     }
 
     fn eval_and_cast_to_bool(&self, nss: &mut NameSpaceStack) -> Result<bool, EvalError> {
@@ -33,147 +173,284 @@ impl Expression {
                 found: Type::from(&v)})
     }
 
-    fn eval_to_address(&self, nss: &mut NameSpaceStack) -> Result<Address, EvalError> {
+    fn eval_and_cast_to_address(&self, nss: &mut NameSpaceStack) -> Result<Address, EvalError> {
+        let v = self.eval(nss)?;
+        v.to_address()
+            .map_err(|_| EvalError::TypeMismatch{
+                expression: self.clone(),
+                expected: Type::Pointer,
+                found: Type::from(&v)})
+    }
+
+    // un indice est un `usize`
+    fn eval_and_cast_to_index(&self, nss: &mut NameSpaceStack) -> Result<i128, EvalError> {
+        match self.eval_and_cast_to_int(nss)? {
+            (i, Type::Usize | Type::IntLiteral) => Ok(i),
+            (_, found) => Err(EvalError::TypeMismatch{expression: self.clone(), expected: Type::Usize, found}),
+        }
+    }
+
+    fn is_place(&self) -> bool {
         match self {
-            Expression::Identifier(id) => {
-                nss.get_address(id)
-            }
-            _ => {
-                Err(EvalError::TypeMismatch {
-                    expression: self.clone(),
-                    expected: Type::from(&Value::from(ParsedValue::Unit)),
-                    found: Type::Unit,
-                })
-            }
+            Expression::Identifier(_) | Expression::Deref(_) => true,
+            Expression::Index(e, _) | Expression::Field(e, _) => e.is_place(),
+            _ => false,
         }
     }
 
-    pub fn eval(&self, nss: &mut NameSpaceStack) -> Result<Value, EvalError> {
+    // adresse de la cellule désignée par une expression gauche (`x`, `*e`,
+    // `e[i]`, `e.i`)
+    fn eval_to_address(&self, nss: &mut NameSpaceStack) -> Result<Address, EvalError> {
         match self {
-            Expression::Const(value) => Ok(Value::from(value.clone())),
-            Expression::Identifier(id) => {
-                Ok(nss.find(id)?) 
+            Expression::Identifier(id) => nss.get_address(id),
+            Expression::Deref(e) => e.eval_and_cast_to_address(nss),
+            Expression::Index(e, i) if e.is_place() => {
+                let (address, container) = e.eval_to_container(nss)?;
+                let index = self.check_index(&container, i, nss)?;
+                Ok(Address::ElementAddress(Box::new(address), index))
             },
-            Expression::BinOp(e1, Binop::Add, e2) => {
-                let v1 = e1.eval_and_cast_to_int(nss)?;
-                let v2 = e2.eval_and_cast_to_int(nss)?;
-                Ok(Value::Integer(v1 + v2))
+            Expression::Field(e, _) if e.is_place() => {
+                let (address, container) = e.eval_to_container(nss)?;
+                let field = self.check_field(&container)?;
+                Ok(Address::ElementAddress(Box::new(address), field))
             },
-            Expression::BinOp(e1, Binop::Sub, e2) => {
-                let v1 = e1.eval_and_cast_to_int(nss)?;
-                let v2 = e2.eval_and_cast_to_int(nss)?;
-                Ok(Value::Integer(v1 - v2))
-            },
-            Expression::BinOp(e1, Binop::Mul, e2) => {
-                let v1 = e1.eval_and_cast_to_int(nss)?;
-                let v2 = e2.eval_and_cast_to_int(nss)?;
-                Ok(Value::Integer(v1 * v2))
+            _ => Err(EvalError::NonAllocatedCell(Some(self.clone()))),
+        }
+    }
+
+    // adresse et valeur du tableau, du Vec ou du tuple désigné par une
+    // expression gauche, à travers les références ; un tuple dont un champ a
+    // été déplacé donne encore accès aux autres
+    fn eval_to_container(&self, nss: &mut NameSpaceStack) -> Result<(Address, Value), EvalError> {
+        let mut address = self.eval_to_address(nss)?;
+        let mut container = nss.peek_at(&address).map_err(|err| err.with_expression_info(self.clone()))?;
+        while let Value::Pointer(pointee) = container {
+            container = nss.read_at(&pointee).map_err(|err| err.with_expression_info(self.clone()))?;
+            address = pointee;
+        }
+        Ok((address, container))
+    }
+
+    // `self` est `e[i]` : l'indice `i` doit désigner un élément de
+    // `container`, la valeur de `e`
+    fn check_index(&self, container: &Value, i: &Expression, nss: &mut NameSpaceStack) -> Result<usize, EvalError> {
+        let len = match (self, nss.read_buffer(container.clone())?) {
+            (_, Value::Array(values)) => values.len(),
+            (Expression::Index(e, _), _) =>
+                return Err(EvalError::CannotIndex{expression: *e.clone(), found: Type::from(container)}),
+            _ => unreachable!("{} is not an indexing", self),
+        };
+        let index = i.eval_and_cast_to_index(nss)?;
+        if 0 <= index && index < len as i128 {
+            Ok(index as usize)
+        } else {
+            Err(EvalError::IndexOutOfBounds{expression: Some(self.clone()), index, len})
+        }
+    }
+
+    // `self` est `e.i` : `container`, la valeur de `e`, doit être un tuple
+    // qui a un champ `i`
+    fn check_field(&self, container: &Value) -> Result<usize, EvalError> {
+        match (self, container) {
+            (Expression::Field(_, i), Value::Tuple(values)) if *i < values.len() => Ok(*i),
+            _ => Err(EvalError::NoField{expression: self.clone(), found: Type::from(container)}),
+        }
+    }
+
+    // le Vec désigné par une expression gauche, modifié en place par `push`
+    // et `pop`
+    fn update_vec<T>(&self, nss: &mut NameSpaceStack,
+        update: impl FnOnce(&mut Vec<Value>, &mut Option<Box<Type>>) -> Result<T, EvalError>) -> Result<T, EvalError> {
+        let (address, container) = self.eval_to_container(nss)?;
+        let Value::Vec(buffer, mut element) = container else {
+            return Err(EvalError::TypeMismatch{expression: self.clone(), expected: Type::Vec(None), found: Type::from(&container)});
+        };
+        let Value::Array(mut values) = nss.read_at(&buffer)? else {
+            unreachable!("the buffer of a Vec holds an array");
+        };
+        let result = update(&mut values, &mut element)?;
+        // le Vec n'est modifié que si la place qui le contient est mutable
+        nss.write_at(&address, Value::Vec(buffer.clone(), element)).map_err(|err| err.with_expression_info(self.clone()))?;
+        nss.write_at(&buffer, Value::Array(values))?;
+        Ok(result)
+    }
+
+    // éléments d'un tableau littéral : leurs types doivent s'accorder, et les
+    // entiers sans suffixe prennent celui des autres
+    fn eval_elements(es: &[Expression], nss: &mut NameSpaceStack) -> Result<Vec<Value>, EvalError> {
+        let values = es.iter().map(|e| e.eval_and_move(nss)).collect::<Result<Vec<_>, _>>()?;
+        let mut ty = values.first().map(Type::from);
+        for (e, value) in es.iter().zip(&values) {
+            let (expected, found) = (ty.unwrap(), Type::from(value));
+            ty = Some(expected.unify(&found)
+                .ok_or_else(|| EvalError::TypeMismatch{expression: e.clone(), expected, found})?);
+        }
+        es.iter().zip(values).map(|(e, value)| with_int_type(value, ty.clone(), e)).collect()
+    }
+
+    // la cellule désignée par l'expression gauche est marquée comme déplacée
+    fn move_from(&self, nss: &mut NameSpaceStack) -> Result<(), EvalError> {
+        let cell = self.eval_to_address(nss)?;
+        nss.move_out(&cell).map_err(|err| err.with_expression_info(self.clone()))
+    }
+
+    // `s + t` consomme la String `s`, dont le tampon reçoit `t`, un &str ou
+    // une référence à une String
+    fn concat(e1: &Expression, v1: Value, e2: &Expression, nss: &mut NameSpaceStack) -> Result<Value, EvalError> {
+        let v2 = match e2.eval(nss)? {
+            Value::Pointer(address) => nss.read_at(&address).map_err(|err| err.with_expression_info(e2.clone()))?,
+            v2 @ Value::Str(_) => v2,
+            v2 => return Err(EvalError::TypeMismatch{expression: e2.clone(), expected: Type::Str, found: Type::from(&v2)}),
+        };
+        let v2 = nss.read_buffer(v2)?;
+        let (Value::String(address), Ok(s2)) = (v1, v2.to_str()) else {
+            return Err(EvalError::TypeMismatch{expression: e2.clone(), expected: Type::Str, found: Type::from(&v2)});
+        };
+        if e1.is_place() {
+            e1.move_from(nss)?;
+        }
+        let s1 = nss.read_at(&address)?.to_str().unwrap();
+        nss.write_at(&address, Value::Str(Rc::from(format!("{}{}", s1, s2))))?;
+        Ok(Value::String(address))
+    }
+
+    // évaluation en position de move (`let x = e`, `e1 = e`, `Box::new(e)`) :
+    // une valeur possédée lue depuis une cellule est déplacée hors de celle-ci
+    fn eval_and_move(&self, nss: &mut NameSpaceStack) -> Result<Value, EvalError> {
+        match self {
+            _ if self.is_place() => {
+                let value = self.eval(nss)?;
+                if nss.is_owned(&value) {
+                    self.move_from(nss)?;
+                }
+                Ok(value)
             },
-            Expression::BinOp(e1, Binop::Div, e2) => {
-                let v1 = e1.eval_and_cast_to_int(nss)?;
-                let v2 = e2.eval_and_cast_to_int(nss)?;
-                Ok(Value::Integer(v1 / v2))
+            Expression::Conditional { cond, cond_true, cond_false } => {
+                if cond.eval_and_cast_to_bool(nss)? {
+                    cond_true.eval_and_move(nss)
+                } else {
+                    cond_false.eval_and_move(nss)
+                }
             },
-            Expression::BinOp(e1, Binop::Mod, e2) => {
-                let v1 = e1.eval_and_cast_to_int(nss)?;
-                let v2 = e2.eval_and_cast_to_int(nss)?;
-                Ok(Value::Integer(v1 % v2))
+            _ => self.eval(nss),
+        }
+    }
+
+    pub fn eval(&self, nss: &mut NameSpaceStack) -> Result<Value, EvalError> {
+        match self {
+            Expression::Const(ParsedValue::Integer(i, t)) if *t != Type::IntLiteral && !t.contains(*i) =>
+                Err(EvalError::Overflow(self.clone())),
+            Expression::Const(value) => Ok(Value::from(value.clone())),
+            Expression::Identifier(id) => {
+                nss.find(id).map_err(|err| err.with_expression_info(self.clone()))
             },
-            Expression::BinOp(e1, Binop::Leq, e2) => {
-                let v1 = e1.eval_and_cast_to_int(nss)?;
-                let v2 = e2.eval_and_cast_to_int(nss)?;
-                let mut boolean = false;
-                if v1 <= v2 {
-                    boolean = true;
-                    Ok(Value::Boolean(boolean))
+            // un entier non signé n'a pas d'opposé, même en mode `--wrapping`
+            Expression::UnOp(Unop::Neg, e) => {
+                let (v, t) = e.eval_and_cast_to_int(nss)?;
+                if !t.is_signed() {
+                    return Err(EvalError::TypeMismatch{expression: *e.clone(), expected: Type::Int, found: t});
                 }
-                else {
-                    Ok(Value::Boolean(boolean))
+                match (-v, nss.is_wrapping()) {
+                    (negated, true) => Ok(Value::Integer(t.wrap(negated), t)),
+                    (negated, false) if t.contains(negated) => Ok(Value::Integer(negated, t)),
+                    _ => Err(EvalError::Overflow(self.clone())),
                 }
             },
-            Expression::BinOp(e1, Binop::Geq, e2) => {
-                let v1 = e1.eval_and_cast_to_int(nss)?;
-                let v2 = e2.eval_and_cast_to_int(nss)?;
-                let mut boolean = false;
-                if v1 <= v2 {
-                    Ok(Value::Boolean(boolean))
-                }
-                else {
-                    boolean = true;
-                    Ok(Value::Boolean(boolean))
-                }
+            // `!` est la négation logique sur un booléen, bit à bit sur un entier
+            Expression::UnOp(Unop::Not, e) => match e.eval(nss)? {
+                Value::Boolean(b) => Ok(Value::Boolean(!b)),
+                Value::Integer(i, t) => Ok(Value::Integer(t.wrap(!i), t)),
+                v => Err(EvalError::TypeMismatch{expression: *e.clone(), expected: Type::Bool, found: Type::from(&v)}),
             },
-            Expression::BinOp(e1, Binop::Lt, e2) => {
-                let v1 = e1.eval_and_cast_to_int(nss)?;
-                let v2 = e2.eval_and_cast_to_int(nss)?;
-                let mut boolean = false;
-                if v1 <= v2 {
-                    boolean = true;
-                    Ok(Value::Boolean(boolean))
-                }
-                else {
-                    Ok(Value::Boolean(boolean))
+            // `&`, `|` et `^` s'appliquent à deux entiers ou à deux booléens
+            Expression::BinOp(e1, op @ (Binop::BitAnd | Binop::BitOr | Binop::BitXor), e2) => {
+                let v1 = e1.eval(nss)?;
+                let v2 = e2.eval(nss)?;
+                match (v1, v2) {
+                    (Value::Integer(i1, t1), Value::Integer(i2, t2)) if t1.unify(&t2).is_some() => Ok(Value::Integer(match op {
+                        Binop::BitAnd => i1 & i2,
+                        Binop::BitOr => i1 | i2,
+                        _ => i1 ^ i2,
+                    }, t1.unify(&t2).unwrap())),
+                    (Value::Boolean(b1), Value::Boolean(b2)) => Ok(Value::Boolean(match op {
+                        Binop::BitAnd => b1 & b2,
+                        Binop::BitOr => b1 | b2,
+                        _ => b1 ^ b2,
+                    })),
+                    (v1 @ (Value::Integer(..) | Value::Boolean(_)), v2) =>
+                        Err(EvalError::TypeMismatch{expression: *e2.clone(), expected: Type::from(&v1), found: Type::from(&v2)}),
+                    (v1, _) => Err(EvalError::TypeMismatch{expression: *e1.clone(), expected: Type::Int, found: Type::from(&v1)}),
                 }
             },
-            Expression::BinOp(e1, Binop::Gt, e2) => {
-                let v1 = e1.eval_and_cast_to_int(nss)?;
-                let v2 = e2.eval_and_cast_to_int(nss)?;
-                let mut boolean = false;
-                if v1 < v2 {
-                    Ok(Value::Boolean(boolean))
-                }
-                else {
-                    boolean = true;
-                    Ok(Value::Boolean(boolean))
-                }
+            // le membre droit d'un décalage peut être de n'importe quel type entier
+            Expression::BinOp(e1, op @ (Binop::Shl | Binop::Shr), e2) => {
+                let (v1, t1) = e1.eval_and_cast_to_int(nss)?;
+                let (v2, _) = e2.eval_and_cast_to_int(nss)?;
+                arithmetic(*op, v1, v2, &t1, nss.is_wrapping())
+                    .map(|i| Value::Integer(i, t1))
+                    .ok_or_else(|| EvalError::Overflow(self.clone()))
             },
-            Expression::BinOp(e1, Binop::Eq, e2) => {
-                let v1 = e1.eval_and_cast_to_int(nss)?;
-                let v2 = e2.eval_and_cast_to_int(nss)?;
-                let mut boolean = false;
-                if v1 == v2 {
-                    boolean = true;
-                    Ok(Value::Boolean(boolean))
-                }
-                else {
-                    Ok(Value::Boolean(boolean))
-                }
+            Expression::BinOp(e1, op @ (Binop::Add | Binop::Sub | Binop::Mul | Binop::Div | Binop::Mod), e2) => {
+                let v1 = e1.eval(nss)?;
+                if let (Binop::Add, Value::String(..)) = (op, &v1) {
+                    return Expression::concat(e1, v1, e2, nss);
+                }
+                let (v1, t1) = e1.cast_to_int(&v1)?;
+                let (v2, t2) = e2.eval_and_cast_to_int(nss)?;
+                let ty = t1.unify(&t2)
+                    .ok_or_else(|| EvalError::TypeMismatch{expression: *e2.clone(), expected: t1, found: t2})?;
+                if v2 == 0 && matches!(op, Binop::Div | Binop::Mod) {
+                    return Err(EvalError::DivisionByZero(*e2.clone()));
+                }
+                arithmetic(*op, v1, v2, &ty, nss.is_wrapping())
+                    .map(|i| Value::Integer(i, ty))
+                    .ok_or_else(|| EvalError::Overflow(self.clone()))
             },
-            Expression::BinOp(e1, Binop::Neq, e2) => {
-                let v1 = e1.eval_and_cast_to_int(nss)?;
-                let v2 = e2.eval_and_cast_to_int(nss)?;
-                let mut boolean = false;
-                if v1 != v2 {
-                    boolean = true;
-                    Ok(Value::Boolean(boolean))
-                }
-                else {
-                    Ok(Value::Boolean(boolean))
-                }
+            // les entiers, les caractères et les chaînes sont ordonnés
+            Expression::BinOp(e1, op @ (Binop::Leq | Binop::Geq | Binop::Lt | Binop::Gt), e2) => {
+                let v1 = e1.eval(nss)?;
+                let v2 = e2.eval(nss)?;
+                let ordering = match (&v1, &v2) {
+                    (Value::Integer(i1, t1), Value::Integer(i2, t2)) if t1.unify(t2).is_some() => i1.cmp(i2),
+                    (Value::Char(c1), Value::Char(c2)) => c1.cmp(c2),
+                    (Value::Str(_) | Value::String(..), Value::Str(_) | Value::String(..)) =>
+                        nss.read_buffer(v1.clone())?.to_str().unwrap().cmp(&nss.read_buffer(v2.clone())?.to_str().unwrap()),
+                    (Value::Integer(..) | Value::Char(_) | Value::Str(_) | Value::String(..), _) =>
+                        return Err(EvalError::TypeMismatch{expression: *e2.clone(), expected: Type::from(&v1), found: Type::from(&v2)}),
+                    _ => return Err(EvalError::TypeMismatch{expression: *e1.clone(), expected: Type::Int, found: Type::from(&v1)}),
+                };
+                Ok(Value::Boolean(match op {
+                    Binop::Leq => ordering.is_le(),
+                    Binop::Geq => ordering.is_ge(),
+                    Binop::Lt => ordering.is_lt(),
+                    _ => ordering.is_gt(),
+                }))
             },
-            Expression::BinOp(e1, Binop::And, e2) => {
-                let v1 = e1.eval_and_cast_to_bool(nss)?;
-                let v2 = e2.eval_and_cast_to_bool(nss)?;
-                let mut boolean = false;
-                if (v1 == true) && (v2==true) {
-                    boolean = true;
-                    Ok(Value::Boolean(boolean))
-                }
-                else {
-                    Ok(Value::Boolean(boolean))
+            // `==` et `!=` comparent deux valeurs quelconques du même type ;
+            // deux pointeurs sont égaux s'ils désignent la même cellule
+            Expression::BinOp(e1, op @ (Binop::Eq | Binop::Neq), e2) => {
+                let v1 = e1.eval(nss)?;
+                let v2 = e2.eval(nss)?;
+                // une String se compare aussi à un &str, un Vec à un tableau de
+                // n'importe quelle longueur
+                let comparable = match (Type::from(&v1), Type::from(&v2)) {
+                    (Type::Str | Type::String, Type::Str | Type::String) => true,
+                    (Type::Array(e1, _) | Type::Vec(e1), Type::Vec(e2)) | (Type::Vec(e1), Type::Array(e2, _)) =>
+                        Type::Vec(e1).unify(&Type::Vec(e2)).is_some(),
+                    (t1, t2) => t1.unify(&t2).is_some(),
+                };
+                if !comparable {
+                    return Err(EvalError::TypeMismatch{expression: *e2.clone(), expected: Type::from(&v1), found: Type::from(&v2)});
                 }
+                Ok(Value::Boolean(equal(&nss.contents(&v1), &nss.contents(&v2)) == (*op == Binop::Eq)))
             },
-            Expression::BinOp(e1, Binop::Or, e2) => {
+            // `&&` et `||` n'évaluent l'opérande droit que si nécessaire
+            Expression::BinOp(e1, op @ (Binop::And | Binop::Or), e2) => {
                 let v1 = e1.eval_and_cast_to_bool(nss)?;
-                let v2 = e2.eval_and_cast_to_bool(nss)?;
-                let mut boolean = false;
-                if (v1 == true) || (v2==true) {
-                    boolean = true;
-                    Ok(Value::Boolean(boolean))
-                }
-                else {
-                    Ok(Value::Boolean(boolean))
+                if v1 == (*op == Binop::Or) {
+                    return Ok(Value::Boolean(v1));
                 }
+                Ok(Value::Boolean(e2.eval_and_cast_to_bool(nss)?))
             },
             Expression::Conditional { cond, cond_true, cond_false } => {
                 let cond_val = cond.eval_and_cast_to_bool(nss)?;
@@ -183,20 +460,128 @@ impl Expression {
                     cond_false.eval(nss)
                 }
             }
-            Expression::AmpersAnd(e) => {
-                if let Expression::Identifier(id) = &**e {
-                    if let Ok(address) = nss.get_address(id) {
-                        Ok(Value::Pointer(address))
-                    } else {
-                        Err(EvalError::Undefined(id.clone()))
-                    }
-                } else {
-                    Err(EvalError::TypeMismatch {
-                        expression: *e.clone(),
-                        expected: Type::Pointer,
-                        found: Type::Unit,
-                    })
+            // `as` tronque comme en Rust ; un booléen devient 0 ou 1, un
+            // caractère son code
+            Expression::Cast(e, t) => match e.eval(nss)? {
+                Value::Integer(i, _) => Ok(Value::Integer(t.wrap(i), t.clone())),
+                Value::Boolean(b) => Ok(Value::Integer(b as i128, t.clone())),
+                Value::Char(c) => Ok(Value::Integer(t.wrap(c as i128), t.clone())),
+                v => Err(EvalError::TypeMismatch{expression: *e.clone(), expected: Type::Int, found: Type::from(&v)}),
+            },
+            // nombre d'éléments, ou longueur en octets d'une chaîne, à travers
+            // les références
+            Expression::Len(e) => {
+                let mut v = e.eval(nss)?;
+                while let Value::Pointer(address) = &v {
+                    v = nss.read_at(address).map_err(|err| err.with_expression_info(*e.clone()))?;
+                }
+                match nss.read_buffer(v)? {
+                    Value::Array(values) => Ok(Value::Integer(values.len() as i128, Type::Usize)),
+                    v => v.to_str()
+                        .map(|s| Value::Integer(s.len() as i128, Type::Usize))
+                        .map_err(|found| EvalError::TypeMismatch{expression: *e.clone(), expected: Type::Str, found}),
+                }
+            },
+            Expression::Array(es) => Ok(Value::Array(Expression::eval_elements(es, nss)?)),
+            // `[e; n]` copie la valeur de `e`, qui ne doit rien posséder
+            Expression::Repeat(e, n) => {
+                let value = with_int_type(e.eval(nss)?, None, e)?;
+                if nss.is_owned(&value) {
+                    return Err(EvalError::NotCopy(*e.clone()));
+                }
+                let n = n.eval_and_cast_to_index(nss)?;
+                let n = usize::try_from(n).map_err(|_| EvalError::Overflow(self.clone()))?;
+                Ok(Value::Array(vec![value; n]))
+            },
+            Expression::VecFrom(e) => match e.eval(nss)? {
+                values @ Value::Array(_) => {
+                    let values = with_int_type(values, None, e)?;
+                    let Type::Array(element, _) = Type::from(&values) else { unreachable!() };
+                    Ok(Value::Vec(nss.malloc(PtrKind::Box, values), element))
+                },
+                v => unreachable!("the grammar gives vec! an array, found {}", v),
+            },
+            Expression::Index(e, _) if e.is_place() => {
+                let address = self.eval_to_address(nss)?;
+                nss.read_at(&address).map_err(|err| err.with_expression_info(self.clone()))
+            },
+            // élément d'un tableau qui n'est pas rangé dans une variable
+            Expression::Index(e, i) => {
+                let mut container = e.eval(nss)?;
+                while let Value::Pointer(address) = &container {
+                    container = nss.read_at(address).map_err(|err| err.with_expression_info(*e.clone()))?;
+                }
+                let index = self.check_index(&container, i, nss)?;
+                match nss.read_buffer(container)? {
+                    Value::Array(values) => Ok(values[index].clone()),
+                    _ => unreachable!(),
+                }
+            },
+            Expression::Tuple(es) => {
+                let values = es.iter().map(|e| e.eval_and_move(nss)).collect::<Result<_, _>>()?;
+                Ok(Value::Tuple(values))
+            },
+            Expression::Field(e, _) if e.is_place() => {
+                let address = self.eval_to_address(nss)?;
+                nss.read_at(&address).map_err(|err| err.with_expression_info(self.clone()))
+            },
+            // champ d'un tuple qui n'est pas rangé dans une variable
+            Expression::Field(e, _) => {
+                let mut container = e.eval(nss)?;
+                while let Value::Pointer(address) = &container {
+                    container = nss.read_at(address).map_err(|err| err.with_expression_info(*e.clone()))?;
                 }
+                let field = self.check_field(&container)?;
+                match container {
+                    Value::Tuple(mut values) => Ok(values.swap_remove(field)),
+                    _ => unreachable!(),
+                }
+            },
+            // l'élément ajouté a le type des autres
+            Expression::Push(e, x) => {
+                let value = x.eval_and_move(nss)?;
+                e.update_vec(nss, |values, element| {
+                    let value = with_int_type(value, element.as_deref().cloned(), x)?;
+                    let found = Type::from(&value);
+                    let ty = match element.take() {
+                        Some(ty) => ty.unify(&found)
+                            .ok_or_else(|| EvalError::TypeMismatch{expression: *x.clone(), expected: *ty, found})?,
+                        None => found,
+                    };
+                    *element = Some(Box::new(ty));
+                    values.push(value);
+                    Ok(Value::UNIT)
+                })
+            },
+            Expression::Pop(e) => e.update_vec(nss, |values, _| {
+                values.pop().ok_or(EvalError::EmptyVec(*e.clone()))
+            }),
+            Expression::StringFrom(e) => {
+                let s = e.eval(nss)?.to_str()
+                    .map_err(|found| EvalError::TypeMismatch{expression: *e.clone(), expected: Type::Str, found})?;
+                Ok(Value::String(nss.malloc(PtrKind::Box, Value::Str(s))))
+            },
+            Expression::AmpersAnd(e) | Expression::AmpersAndMut(e) => {
+                Ok(Value::Pointer(e.eval_to_address(nss)?))
+            }
+            Expression::NewPtr(kind, e) => {
+                let value = with_int_type(e.eval_and_move(nss)?, None, e)?;
+                Ok(Value::Pointer(nss.malloc(*kind, value)))
+            }
+            Expression::Deref(e) => {
+                let address = e.eval_and_cast_to_address(nss)?;
+                nss.read_at(&address).map_err(|err| err.with_expression_info(self.clone()))
+            }
+            Expression::RawAlloc => {
+                Ok(Value::Pointer(nss.malloc_uninitialized(PtrKind::RawPtr)))
+            }
+            Expression::RcClone(e) => {
+                let address = e.eval_and_cast_to_address(nss)?;
+                if nss.ptr_kind(&address) != Some(PtrKind::Rc) {
+                    return Err(EvalError::NotReferenceCounted(*e.clone()));
+                }
+                nss.rc_clone(&address).map_err(|err| err.with_expression_info(*e.clone()))?;
+                Ok(Value::Pointer(address))
             }
         }
     }
@@ -208,26 +593,77 @@ impl Instruction {
 
         match self {
             Instruction::Expr(expr) => {
-                let entier = expr.eval(nss)?;
+                let entier = with_int_type(expr.eval(nss)?, None, expr)?;
                 Ok((None, entier))
             }
-            Instruction::Let { id, mutable, expr } => {
-                let entier = expr.eval(nss)?;
-                let ismut = mutable;
-                let id = &Identifier::from(id.as_str());
-                let _def = nss.declare(id, *ismut, &entier);
-                Ok((Some(id.clone()), entier.clone()))
+            // chaque variable du motif a sa propre cellule, mutable ou non
+            Instruction::Let { pattern, ty, expr } => {
+                let typed = ty.clone().map(|ty| with_literal_type(expr, ty));
+                let entier = with_int_type(typed.as_ref().unwrap_or(expr).eval_and_move(nss)?, ty.clone(), expr)?;
+                if let Some(ty) = ty.as_ref().filter(|ty| **ty != Type::from(&entier)) {
+                    return Err(EvalError::TypeMismatch{expression: expr.clone(), expected: ty.clone(), found: Type::from(&entier)});
+                }
+                let mut bindings = vec![];
+                destructure(pattern, entier.clone(), &mut bindings)?;
+                for (id, mutable, value) in bindings {
+                    nss.declare(id, mutable, &value)?;
+                }
+                let id = match pattern {
+                    Pattern::Identifier{id, ..} => Some(id.clone()),
+                    Pattern::Tuple(_) => None,
+                };
+                Ok((id, entier))
             }
             Instruction::Block(vec) => {
                 nss.push(NameSpace::new());
-                let mut return_value = Value::Unit;
+                let mut return_value = Value::UNIT;
                 for instr in vec {
-                    let (_id, val) = instr.exec(nss).map_err(|err| {nss.pop(); err})?;
+                    let (_id, val) = instr.exec(nss).inspect_err(|_| {nss.pop();})?;
                     return_value = val;
                 };
                 nss.pop();
                 Ok((None, return_value))
             }
+            Instruction::WriteAt(lexpr, expr) => {
+                let value = expr.eval_and_move(nss)?;
+                let address = lexpr.eval_to_address(nss)?;
+                let old = nss.read_at(&address).ok();
+                let value = with_int_type(value, old.as_ref().map(Type::from), expr)?;
+                nss.write_at(&address, value).map_err(|err| err.with_expression_info(lexpr.clone()))?;
+                // l'ancienne valeur est écrasée : les Box qu'elle possédait sont libérées
+                if let Some(old) = old {
+                    nss.drop_value(&old);
+                }
+                Ok((None, Value::UNIT))
+            }
+            Instruction::Free(expr) => {
+                let address = expr.eval_and_cast_to_address(nss)?;
+                if let Some(PtrKind::Box | PtrKind::Rc) = nss.ptr_kind(&address) {
+                    return Err(EvalError::CannotFreeOwnedValue(Some(expr.clone())));
+                }
+                nss.free(&address).map_err(|err| err.with_expression_info(expr.clone()))?;
+                Ok((None, Value::UNIT))
+            }
+            Instruction::Drop(expr) => {
+                let value = expr.eval_and_move(nss)?;
+                nss.drop_value(&value);
+                Ok((None, Value::UNIT))
+            }
+            // les arguments sont tous évalués avant d'écrire quoi que ce soit
+            Instruction::Print { newline, pieces, args } => {
+                let values = args.iter().map(|arg| arg.eval(nss).map(|value| nss.contents(&value))).collect::<Result<Vec<_>, _>>()?;
+                let mut text: String = pieces.iter()
+                    .map(|piece| match piece {
+                        Piece::Text(text) => text.clone(),
+                        Piece::Arg(i, spec) => render(&values[*i], spec),
+                    })
+                    .collect();
+                if *newline {
+                    text.push('\n');
+                }
+                nss.print(&text);
+                Ok((None, Value::UNIT))
+            }
         }
     }
 }
@@ -296,7 +732,7 @@ Expression::BinOp(e1, Binop::Leq, e2) => {
             Expression::BinOp(e1, Binop::And, e2) => {
                 let v1 = e1.eval_and_cast_to_int(nss)?;
                 let v2 = e2.eval_and_cast_to_int(nss)?;
-                if (v1 == true) && (v2==true) {
+                if v1 && v2 {
                     true
                 }
                 else {
@@ -306,7 +742,7 @@ Expression::BinOp(e1, Binop::Leq, e2) => {
             Expression::BinOp(e1, Binop::Or, e2) => {
                 let v1 = e1.eval_and_cast_to_int(nss)?;
                 let v2 = e2.eval_and_cast_to_int(nss)?;
-                if (v1 == true) || (v2==true) {
+                if v1 || v2 {
                     true
                 }
                 else {
@@ -3,12 +3,27 @@ use crate::parser::ParseError;
 use crate::binop::Binop;
 use crate::parsing::leftexpression::LeftExpression;
 use crate::parsing::parsedvalue::ParsedValue;
+use crate::parsing::ptr_kind::PtrKind;
+use crate::unop::Unop;
+use crate::r#type::Type;
 
 ////////////////////////////////////////////////////////////////////////////////
 #[derive(Debug, Clone)]
 pub enum Expression {
     Const(ParsedValue),
     Identifier(Identifier),
+    UnOp(Unop, Box<Expression>),
+    Cast(Box<Expression>, Type),
+    Len(Box<Expression>),
+    StringFrom(Box<Expression>),
+    Array(Vec<Expression>),
+    Repeat(Box<Expression>, Box<Expression>),
+    VecFrom(Box<Expression>),
+    Index(Box<Expression>, Box<Expression>),
+    Push(Box<Expression>, Box<Expression>),
+    Pop(Box<Expression>),
+    Tuple(Vec<Expression>),
+    Field(Box<Expression>, usize),
     BinOp(Box<Expression>, Binop, Box<Expression>),
     Conditional{
         cond: Box<Expression>,
@@ -16,6 +31,11 @@ pub enum Expression {
         cond_false: Box<Expression>,
     },
     AmpersAnd(Box<Expression>),
+    AmpersAndMut(Box<Expression>),
+    NewPtr(PtrKind, Box<Expression>),
+    RcClone(Box<Expression>),
+    RawAlloc,
+    Deref(Box<Expression>),
 }
 ////////////////////////////////////////////////////////////////////////////////
 
@@ -23,15 +43,41 @@ pub enum Expression {
 
 use std::fmt::Display;
 
+// opérande d'un opérateur postfixe, parenthésé s'il n'est pas une variable,
+// un élément ou un champ
+fn postfix_operand(e: &Expression) -> String {
+    match e {
+        Expression::Identifier(_) | Expression::Index(..) | Expression::Field(..) => e.to_string(),
+        _ => format!("({})", e),
+    }
+}
+
 impl Display for Expression {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         use Expression::*;
         match self {
             Const(i) => write!(f, "{}", i),
             Identifier(i) => write!(f, "{}", i),
+            UnOp(op, e) => write!(f, "{}{}", op, e),
+            Cast(e, t) => write!(f, "({} as {})", e, t),
+            Len(e) => write!(f, "({}).len()", e),
+            StringFrom(e) => write!(f, "String::from({})", e),
+            Array(es) => write!(f, "[{}]", es.iter().map(|e| e.to_string()).collect::<Vec<_>>().join(", ")),
+            Repeat(e, n) => write!(f, "[{}; {}]", e, n),
+            VecFrom(e) => write!(f, "vec!{}", e),
+            Index(e, i) => write!(f, "{}[{}]", postfix_operand(e), i),
+            Push(e, x) => write!(f, "{}.push({})", postfix_operand(e), x),
+            Pop(e) => write!(f, "{}.pop()", postfix_operand(e)),
+            Tuple(es) => write!(f, "{}", crate::r#type::tuple(es)),
+            Field(e, i) => write!(f, "{}.{}", postfix_operand(e), i),
             BinOp(lhs, op, rhs) => write!(f, "({} {} {})", lhs, op, rhs),
             Conditional { cond, cond_true, cond_false } => write!(f, "({}) ? {}  : {} ", cond, cond_true, cond_false),
             AmpersAnd(e) => write!(f, "&{}", e),
+            AmpersAndMut(e) => write!(f, "&mut {}", e),
+            NewPtr(kind, e) => write!(f, "{}::new({})", kind, e),
+            RcClone(e) => write!(f, "Rc::clone(&{})", e),
+            RawAlloc => write!(f, "RawPtr::new()"),
+            Deref(e) => write!(f, "*{}", e),
         }
     }
 }
@@ -39,13 +85,64 @@ impl Display for Expression {
 
 use crate::parsing::expression::Expression as ParseExpression;
 
+impl From<LeftExpression> for Result<Expression, ParseError> {
+    fn from(lexpr: LeftExpression) -> Self {
+        match lexpr {
+            LeftExpression::Identifier(id) => Ok(Expression::Identifier(id)),
+            LeftExpression::Star(lexpr) => Ok(Expression::Deref(Box::new(Self::from(*lexpr)?))),
+            LeftExpression::Index(lexpr, i) => Ok(Expression::Index(Box::new(Self::from(*lexpr)?), Box::new(Self::from(*i)?))),
+            LeftExpression::Field(lexpr, i) => Ok(Expression::Field(Box::new(Self::from(*lexpr)?), i)),
+        }
+    }
+}
+
 impl From<ParseExpression> for Result<Expression, ParseError> {
     fn from(expr: ParseExpression) -> Self {
         match expr {
             ParseExpression::Const(v) => 
                 Ok(Expression::Const(v)),
-            ParseExpression::ValueAt(LeftExpression::Identifier(id)) => 
-                Ok(Expression::Identifier(id.clone())),
+            ParseExpression::ValueAt(lexpr) => 
+                Self::from(lexpr),
+            ParseExpression::NewPtr(kind, expr) =>
+                Ok(Expression::NewPtr(kind, Box::new(Self::from(*expr)?))),
+            ParseExpression::RawAlloc =>
+                Ok(Expression::RawAlloc),
+            ParseExpression::AmpersAnd(lexpr) =>
+                Ok(Expression::AmpersAnd(Box::new(Self::from(lexpr)?))),
+            ParseExpression::AmpersAndMut(lexpr) =>
+                Ok(Expression::AmpersAndMut(Box::new(Self::from(lexpr)?))),
+            ParseExpression::RcClone(lexpr) =>
+                Ok(Expression::RcClone(Box::new(Self::from(lexpr)?))),
+            ParseExpression::UnOp(unop, e) => {
+                let unop: Result<Unop, ParseError> = <_>::from(unop);
+                Ok(Expression::UnOp(unop?, Box::new(Self::from(*e)?)))
+            },
+            ParseExpression::Cast(e, t) =>
+                Ok(Expression::Cast(Box::new(Self::from(*e)?), t)),
+            ParseExpression::Len(e) =>
+                Ok(Expression::Len(Box::new(Self::from(*e)?))),
+            ParseExpression::StringFrom(e) =>
+                Ok(Expression::StringFrom(Box::new(Self::from(*e)?))),
+            ParseExpression::Array(es) => {
+                let es: Result<Vec<Expression>, ParseError> = es.into_iter().map(Self::from).collect();
+                Ok(Expression::Array(es?))
+            },
+            ParseExpression::Repeat(e, n) =>
+                Ok(Expression::Repeat(Box::new(Self::from(*e)?), Box::new(Self::from(*n)?))),
+            ParseExpression::VecFrom(e) =>
+                Ok(Expression::VecFrom(Box::new(Self::from(*e)?))),
+            ParseExpression::Index(e, i) =>
+                Ok(Expression::Index(Box::new(Self::from(*e)?), Box::new(Self::from(*i)?))),
+            ParseExpression::Push(e, x) =>
+                Ok(Expression::Push(Box::new(Self::from(*e)?), Box::new(Self::from(*x)?))),
+            ParseExpression::Pop(e) =>
+                Ok(Expression::Pop(Box::new(Self::from(*e)?))),
+            ParseExpression::Tuple(es) => {
+                let es: Result<Vec<Expression>, ParseError> = es.into_iter().map(Self::from).collect();
+                Ok(Expression::Tuple(es?))
+            },
+            ParseExpression::Field(e, i) =>
+                Ok(Expression::Field(Box::new(Self::from(*e)?), i)),
             ParseExpression::BinOp(lhs, binop, rhs) => {
                 let lhs = Box::new(Self::from(*lhs)?);
                 let rhs = Box::new(Self::from(*rhs)?);
@@ -59,7 +156,6 @@ impl From<ParseExpression> for Result<Expression, ParseError> {
                     cond_false: Box::new(Self::from(*cond_false)?) 
                 })
             }
-            _ => { Err(ParseError::SyntaxNotSupported) }            
         }
     }
 }
@@ -1,20 +1,30 @@
 use std::{fmt::{Display, Debug}, rc::Rc};
 
+use pest::Parser;
+
+use crate::parsing::utils::{PestParser, Rule};
+
 ////////////////////////////////////////////////////////////////////////////
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Identifier (Rc<str>);
 ////////////////////////////////////////////////////////////////////////////
 
 
+// `r#nom` désigne l'identificateur `nom`
 impl From<&str> for Identifier {
     fn from(s: &str) -> Self {
-        Identifier(Rc::from(s))
+        Identifier(Rc::from(s.strip_prefix("r#").unwrap_or(s)))
     }
 }
 
+// un mot-clé de la grammaire (règle `keyword`) s'affiche `r#nom`, pour que
+// l'affichage puisse être relu par le parser
 impl Display for Identifier {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        match PestParser::parse(Rule::keyword, &self.0) {
+            Ok(pairs) if pairs.as_str() == &*self.0 => write!(f, "r#{}", self.0),
+            _ => write!(f, "{}", self.0),
+        }
     }
 }
 
@@ -1,11 +1,17 @@
-use crate::identifier::Identifier;
 use crate::expression::Expression;
+use crate::parsing::format::{self, Piece};
+use crate::parsing::pattern::Pattern;
+use crate::r#type::Type;
 
 #[derive(Debug)]
 pub enum Instruction {
     Expr(Expression),
-    Let{id:String, mutable: bool, expr:Expression},
+    Let{pattern: Pattern, ty: Option<Type>, expr:Expression},
     Block(Vec<Instruction>),
+    WriteAt(Expression, Expression),
+    Free(Expression),
+    Drop(Expression),
+    Print{newline: bool, pieces: Vec<Piece>, args: Vec<Expression>},
 } 
 
 use std::fmt::Display;
@@ -14,19 +20,26 @@ impl Display for Instruction {
         use Instruction::*;
         match self {
             Expr(expr) => write!(f, "{}", expr),
-            Let{id, mutable, expr} => {
-                if *mutable {
-                    write!(f, "let mut {} = {}", id, expr)
-                } else {
-                    write!(f, "let {} = {}", id, expr)
-                }
+            Let{pattern, ty, expr} => {
+                let ty = ty.as_ref().map(|t| format!(": {}", t)).unwrap_or_default();
+                write!(f, "let {}{} = {}", pattern, ty, expr)
             },
             Block(instrs) => { 
-                write!(f, "{{{}}}", instrs.into_iter()
+                write!(f, "{{{}}}", instrs.iter()
                                           .map(|x| x.to_string())
                                           .collect::<Vec<_>>()
                                           .join(";"))
             },
+            WriteAt(lexpr, expr) => write!(f, "{} = {}", lexpr, expr),
+            Free(expr) => write!(f, "free({})", expr),
+            Drop(expr) => write!(f, "drop({})", expr),
+            Print{newline, pieces, args} => {
+                write!(f, "{}!({:?}", if *newline { "println" } else { "print" }, format::format_string(pieces))?;
+                for arg in args {
+                    write!(f, ", {}", arg)?;
+                }
+                write!(f, ")")
+            },
         }
     }
 }
@@ -38,11 +51,18 @@ impl From<ParseInstruction> for Result<Instruction, ParseError> {
     fn from(instr: ParseInstruction) -> Self {
         match instr {
             ParseInstruction::Expr(expr) => Ok(Instruction::Expr(Expression::parse(&expr.to_string())?)),
-            ParseInstruction::Let{id, mutable, expr, ..} => Ok(Instruction::Let{id: id.to_string(), mutable, expr: Expression::parse(&expr.to_string())?}),
+            ParseInstruction::Let{pattern, ty, expr} => Ok(Instruction::Let{pattern, ty, expr: Expression::parse(&expr.to_string())?}),
             ParseInstruction::Block(instrs) => {
-                let instrs: Result<Vec<Instruction>, ParseError> = instrs.into_iter().map(|x| <_>::from(x)).collect();
+                let instrs: Result<Vec<Instruction>, ParseError> = instrs.into_iter().map(<_>::from).collect();
                 Ok(Instruction::Block(instrs?))
             },
+            ParseInstruction::WriteAt(lexpr, expr) => Ok(Instruction::WriteAt(Result::from(lexpr)?, Expression::parse(&expr.to_string())?)),
+            ParseInstruction::Free(lexpr) => Ok(Instruction::Free(Result::from(lexpr)?)),
+            ParseInstruction::Drop(lexpr) => Ok(Instruction::Drop(Result::from(lexpr)?)),
+            ParseInstruction::Print{newline, pieces, args} => {
+                let args: Result<Vec<Expression>, ParseError> = args.iter().map(|arg| Expression::parse(&arg.to_string())).collect();
+                Ok(Instruction::Print{newline, pieces, args: args?})
+            },
             _ => { Err(ParseError::SyntaxNotSupported) }
         }
     }
@@ -3,6 +3,7 @@ mod parser;
 mod error;
 mod identifier;
 mod binop;
+mod unop;
 mod expression;
 mod eval;
 mod namespace;
@@ -11,10 +12,28 @@ mod namespacestack;
 mod value;
 mod r#type;
 #[cfg(test)]
+mod test_utils;
+#[cfg(test)]
 mod test_namespace;
+#[cfg(test)]
 mod test_namespace_stack;
+#[cfg(test)]
+mod test_borrowck;
+#[cfg(test)]
+mod test_arithmetic;
+#[cfg(test)]
+mod test_logic;
+#[cfg(test)]
+mod test_strings;
+#[cfg(test)]
+mod test_print;
+#[cfg(test)]
+mod test_arrays;
+#[cfg(test)]
+mod test_tuples;
 mod memorycell;
 mod memory;
+mod borrowck;
 
 use std::io::{self, BufRead, Write};
 use namespacestack::NameSpaceStack;
@@ -25,31 +44,42 @@ use crate::error::Error;
 use crate::identifier::Identifier;
 use crate::value::Value;
 use crate::r#type::Type;
+use crate::borrowck::BorrowEnv;
 
 fn prompt() {
     print!("µRust # ");
     io::stdout().flush().unwrap();
 }
 
-fn parse_exec(input: &str, nss: &mut NameSpaceStack) -> Result<(Option<Identifier>, Value), Error> {
-    match Instruction::parse(input) {
-        Ok(mut instr) => {
-            instr.exec(nss).map_err(|err| Error::EvalError(err))
-        }
-        Err(e) => Err(Error::ParseError(e)),
-    }
+// l'instruction n'est exécutée que si ses emprunts sont valides ; les
+// emprunts ne sont enregistrés que si l'exécution réussit
+fn parse_exec(input: &str, env: &mut BorrowEnv, nss: &mut NameSpaceStack) -> Result<(Option<Identifier>, Value), Error> {
+    let mut instr = Instruction::parse(input)?;
+    let mut checked_env = env.clone();
+    instr.borrowck(&mut checked_env).map_err(Error::BorrowError)?;
+    let result = instr.exec(nss)?;
+    *env = checked_env;
+    Ok(result)
 }
 
 fn main(){
     prompt();
+    let mut env = BorrowEnv::new();
     let mut nss = NameSpaceStack::new();
+    nss.set_wrapping(std::env::args().any(|arg| arg == "--wrapping"));
     nss.push(NameSpace::new());
     let stdin = io::stdin().lock();
     for line in stdin.lines() {
         let line = line.unwrap();
-        match parse_exec(&line, &mut nss) {
+        let result = parse_exec(&line, &mut env, &mut nss);
+        // la sortie du programme reste sur ses propres lignes
+        if nss.close_line() {
+            println!();
+        }
+        match result {
             Ok((id, val)) => {
-                println!("{} : {} = {}", id.unwrap_or(Identifier::from("-")), Type::from(&val), val);
+                let count = nss.strong_count(&val).map(|n| format!(" (strong count: {})", n)).unwrap_or_default();
+                println!("{} : {} = {}{}", id.unwrap_or(Identifier::from("-")), Type::from(&val), nss.contents(&val), count);
             }
             Err(e) => {
                 println!("{}", e);
@@ -57,6 +87,7 @@ fn main(){
         }
         prompt();
     }
+    nss.pop();
 }
 
 /*
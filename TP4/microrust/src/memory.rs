@@ -0,0 +1,110 @@
+use std::fmt::{self, Display};
+
+use crate::{error::EvalError, identifier::Identifier, value::Value};
+use crate::memorycell::MemoryCell;
+use crate::parsing::ptr_kind::PtrKind;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(clippy::enum_variant_names)]
+pub enum Address {
+    StackAddress(usize, Identifier),
+    HeapAddress(usize),
+    // élément d'un tableau, d'un Vec ou d'un tuple rangé à l'adresse donnée
+    ElementAddress(Box<Address>, usize),
+}
+
+impl Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Address::*;
+        match self {
+            StackAddress(depth, id) => write!(f, "@{}:{}", depth, id),
+            HeapAddress(i) => write!(f, "@heap:{}", i),
+            ElementAddress(address, i) => write!(f, "{}[{}]", address, i),
+        }
+    }
+}
+
+// le tas : une cellule à `None` a été libérée
+// `kinds[i]` est le genre de pointeur qui a alloué la cellule `i`
+// `counts[i]` est le nombre de références fortes vers la cellule `i` (Rc)
+#[derive(Debug)]
+pub struct Memory {
+    cells: Vec<Option<MemoryCell>>,
+    kinds: Vec<PtrKind>,
+    counts: Vec<usize>,
+}
+
+impl Memory {
+    pub fn new() -> Self {
+        Memory { cells: vec![], kinds: vec![], counts: vec![] }
+    }
+
+    pub fn malloc(&mut self, kind: PtrKind, value: Value) -> Address {
+        self.cells.push(Some(MemoryCell::new(true, value)));
+        self.kinds.push(kind);
+        self.counts.push(1);
+        Address::HeapAddress(self.cells.len() - 1)
+    }
+
+    pub fn malloc_uninitialized(&mut self, kind: PtrKind) -> Address {
+        self.cells.push(Some(MemoryCell::new_uninitialized(true)));
+        self.kinds.push(kind);
+        self.counts.push(1);
+        Address::HeapAddress(self.cells.len() - 1)
+    }
+
+    pub fn strong_count(&self, i: usize) -> Result<usize, EvalError> {
+        self.get(i)?;
+        Ok(self.counts[i])
+    }
+
+    pub fn increment(&mut self, i: usize) -> Result<usize, EvalError> {
+        self.get(i)?;
+        self.counts[i] += 1;
+        Ok(self.counts[i])
+    }
+
+    pub fn decrement(&mut self, i: usize) -> Result<usize, EvalError> {
+        self.get(i)?;
+        self.counts[i] -= 1;
+        Ok(self.counts[i])
+    }
+
+    pub fn kind(&self, i: usize) -> Option<PtrKind> {
+        self.kinds.get(i).copied()
+    }
+
+    pub fn get(&self, i: usize) -> Result<&MemoryCell, EvalError> {
+        match self.cells.get(i) {
+            Some(Some(cell)) => Ok(cell),
+            Some(None) => Err(EvalError::UseAfterFree(None)),
+            None => Err(EvalError::NonAllocatedCell(None)),
+        }
+    }
+
+    pub fn get_mut(&mut self, i: usize) -> Result<&mut MemoryCell, EvalError> {
+        match self.cells.get_mut(i) {
+            Some(Some(cell)) => Ok(cell),
+            Some(None) => Err(EvalError::UseAfterFree(None)),
+            None => Err(EvalError::NonAllocatedCell(None)),
+        }
+    }
+
+    pub fn read(&self, i: usize) -> Result<Value, EvalError> {
+        self.get(i)?.get_value()
+    }
+
+    pub fn write(&mut self, i: usize, value: Value) -> Result<(), EvalError> {
+        self.get_mut(i)?.set_value(value)
+    }
+
+    pub fn move_out(&mut self, i: usize) -> Result<(), EvalError> {
+        self.get_mut(i)?.move_out()
+    }
+
+    pub fn free(&mut self, i: usize) -> Result<(), EvalError> {
+        self.get(i)?;
+        self.cells[i] = None;
+        Ok(())
+    }
+}
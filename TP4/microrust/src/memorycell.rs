@@ -0,0 +1,142 @@
+use crate::{error::EvalError, value::Value};
+
+#[derive(Debug)]
+pub struct MemoryCell {
+    mutable: bool,
+    moved: bool,
+    // chemins des champs de tuple déplacés hors de la valeur
+    moved_fields: Vec<Vec<usize>>,
+    value: Option<Value>,
+}
+
+impl MemoryCell {
+    pub fn new(mutable: bool, value: Value) -> MemoryCell {
+        MemoryCell {
+            mutable,
+            moved: false,
+            moved_fields: vec![],
+            value: Some(value),
+        }
+    }
+
+    pub fn new_uninitialized(mutable: bool) -> MemoryCell {
+        MemoryCell {
+            mutable,
+            moved: false,
+            moved_fields: vec![],
+            value: None,
+        }
+    }
+
+    pub fn is_mutable(&self) -> bool {
+        self.mutable
+    }
+
+    pub fn get_value(&self) -> Result<Value, EvalError> {
+        self.get_field(&[])
+    }
+
+    pub fn set_value(&mut self, value: Value) -> Result<(), EvalError> {
+        if self.is_mutable() {
+            self.value = Some(value);
+            self.moved = false;
+            self.moved_fields.clear();
+            Ok(())
+        }
+        else {Err(EvalError::NotMutable(None))}
+    }
+
+    pub fn move_out(&mut self) -> Result<(), EvalError> {
+        self.get_value()?;
+        self.moved = true;
+        Ok(())
+    }
+
+    // élément désigné par `path` dans la valeur, à travers les tableaux et
+    // les tuples ; ses propres champs ont pu être déplacés
+    pub fn field(&self, path: &[usize]) -> Result<&Value, EvalError> {
+        if self.moved || self.moved_fields.iter().any(|moved| path.starts_with(moved)) {
+            return Err(EvalError::MovedValue(None));
+        }
+        let mut value = self.value.as_ref().ok_or(EvalError::NonInitializedValue(None))?;
+        for i in path {
+            value = match value {
+                Value::Array(values) | Value::Tuple(values) => values.get(*i)
+                    .ok_or(EvalError::IndexOutOfBounds{expression: None, index: *i as i128, len: values.len()})?,
+                _ => return Err(EvalError::NonAllocatedCell(None)),
+            };
+        }
+        Ok(value)
+    }
+
+    // une valeur dont un champ a été déplacé ne peut plus être lue entière
+    pub fn get_field(&self, path: &[usize]) -> Result<Value, EvalError> {
+        let value = self.field(path)?;
+        if self.moved_fields.iter().any(|moved| moved.starts_with(path)) {
+            return Err(EvalError::MovedValue(None));
+        }
+        Ok(value.clone())
+    }
+
+    // écrire un champ déplacé le réinitialise
+    pub fn set_field(&mut self, path: &[usize], value: Value) -> Result<(), EvalError> {
+        let Some((last, parent)) = path.split_last() else {
+            return self.set_value(value);
+        };
+        if !self.is_mutable() {
+            return Err(EvalError::NotMutable(None));
+        }
+        self.field(parent)?;
+        let mut target = self.value.as_mut().unwrap();
+        for i in parent {
+            target = match target {
+                Value::Array(values) | Value::Tuple(values) => &mut values[*i],
+                _ => unreachable!(),
+            };
+        }
+        match target {
+            Value::Array(values) | Value::Tuple(values) if *last < values.len() => values[*last] = value,
+            Value::Array(values) | Value::Tuple(values) =>
+                return Err(EvalError::IndexOutOfBounds{expression: None, index: *last as i128, len: values.len()}),
+            _ => return Err(EvalError::NonAllocatedCell(None)),
+        }
+        self.moved_fields.retain(|moved| !moved.starts_with(path));
+        Ok(())
+    }
+
+    // seuls les champs de tuple peuvent être déplacés : un élément de
+    // tableau ne peut pas manquer
+    pub fn move_field(&mut self, path: &[usize]) -> Result<(), EvalError> {
+        if path.is_empty() {
+            return self.move_out();
+        }
+        for n in 0..path.len() {
+            if !matches!(self.field(&path[..n])?, Value::Tuple(_)) {
+                return Err(EvalError::CannotMoveOutOfIndex(None));
+            }
+        }
+        self.get_field(path)?;
+        self.moved_fields.push(path.to_vec());
+        Ok(())
+    }
+
+    // valeur que la cellule possède encore, à libérer avec elle : ses champs
+    // déplacés sont remplacés par `()`
+    pub fn owned_value(&self) -> Option<Value> {
+        if self.moved {
+            return None;
+        }
+        let mut value = self.value.clone()?;
+        for path in &self.moved_fields {
+            let mut target = &mut value;
+            for i in path {
+                target = match target {
+                    Value::Tuple(values) => &mut values[*i],
+                    _ => unreachable!(),
+                };
+            }
+            *target = Value::UNIT;
+        }
+        Some(value)
+    }
+}
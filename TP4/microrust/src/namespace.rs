@@ -2,7 +2,6 @@ use crate::error::EvalError;
 use crate::identifier::Identifier;
 use crate::value::Value;
 use crate::memorycell::MemoryCell;
-use crate::memory::Address;
 use std::collections::HashMap;
 
 pub struct NameSpace(HashMap<Identifier, MemoryCell>);
@@ -15,7 +14,7 @@ impl NameSpace {
 
     pub fn declare(&mut self, id: &Identifier, memcell: MemoryCell) -> Result<(), EvalError> {
 //        self.0.try_insert(id, value).map_err(|_| EvalError::AlreadyDefined(id))
-        if self.0.contains_key(&id) {
+        if self.0.contains_key(id) {
             Err(EvalError::AlreadyDefined(id.clone()))
         } else {
             self.0.insert(id.clone(), memcell);
@@ -25,21 +24,40 @@ impl NameSpace {
 
     pub fn find(&self, id: &Identifier) -> Result<Value, EvalError> {
         match self.0.get(id) {
-            Some(MemoryCell) => MemoryCell.get_value(),
+            Some(memory_cell) => memory_cell.get_value(),
             None => Err(EvalError::Undefined(id.clone())),
         }
     }
 
     pub fn set(&mut self, id: &Identifier, value: Value) -> Result<(), EvalError> {
         match self.0.get_mut(id) {
-            Some(MemoryCell) => {
-                MemoryCell.set_value(value)
+            Some(memory_cell) => {
+                memory_cell.set_value(value)
             },
             None => Err(EvalError::Undefined(id.clone())),
         }
     }
 
+    pub fn move_out(&mut self, id: &Identifier) -> Result<(), EvalError> {
+        match self.0.get_mut(id) {
+            Some(memory_cell) => memory_cell.move_out(),
+            None => Err(EvalError::Undefined(id.clone())),
+        }
+    }
+
+    pub fn cell(&self, id: &Identifier) -> Result<&MemoryCell, EvalError> {
+        self.0.get(id).ok_or(EvalError::Undefined(id.clone()))
+    }
+
+    pub fn cell_mut(&mut self, id: &Identifier) -> Result<&mut MemoryCell, EvalError> {
+        self.0.get_mut(id).ok_or(EvalError::Undefined(id.clone()))
+    }
+
     pub fn contains_key(&self, id: &Identifier) -> bool {
         self.0.contains_key(id)
     }
-}
\ No newline at end of file
+
+    pub fn values(&self) -> impl Iterator<Item = Value> + '_ {
+        self.0.values().filter_map(MemoryCell::owned_value)
+    }
+}
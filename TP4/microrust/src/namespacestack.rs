@@ -0,0 +1,294 @@
+use crate::{error::EvalError, identifier::Identifier, namespace::NameSpace, value::Value};
+use crate::memorycell::MemoryCell;
+use crate::memory::{Address, Memory};
+use crate::parsing::ptr_kind::PtrKind;
+
+// `frames[i]` identifie de façon unique le i-ème NameSpace empilé : une
+// adresse de pile dont le frame a été dépilé est une référence pendante
+pub struct NameSpaceStack {
+    stack: Vec<NameSpace>,
+    frames: Vec<usize>,
+    next_frame: usize,
+    heap: Memory,
+    // l'arithmétique déborde silencieusement au lieu d'échouer
+    wrapping: bool,
+    // sortie de `print!`, gardée au lieu d'être écrite sur stdout si capturée
+    captured: Option<String>,
+    // la dernière sortie ne finit pas par un retour à la ligne
+    open_line: bool,
+}
+
+impl NameSpaceStack {
+    pub fn new() -> Self {
+        NameSpaceStack { stack: vec![], frames: vec![], next_frame: 0, heap: Memory::new(), wrapping: false, captured: None, open_line: false }
+    }
+
+    pub fn set_wrapping(&mut self, wrapping: bool) {
+        self.wrapping = wrapping;
+    }
+
+    pub fn is_wrapping(&self) -> bool {
+        self.wrapping
+    }
+
+    pub fn print(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        match &mut self.captured {
+            Some(captured) => captured.push_str(text),
+            None => {
+                print!("{}", text);
+                std::io::Write::flush(&mut std::io::stdout()).unwrap();
+            },
+        }
+        self.open_line = !text.ends_with('\n');
+    }
+
+    pub fn capture_output(&mut self) {
+        self.captured = Some(String::new());
+    }
+
+    pub fn take_output(&mut self) -> String {
+        self.captured.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    // vrai si la sortie s'est arrêtée au milieu d'une ligne, que l'appelant
+    // doit terminer avant d'écrire autre chose
+    pub fn close_line(&mut self) -> bool {
+        std::mem::replace(&mut self.open_line, false)
+    }
+
+    pub fn push(&mut self, ns: NameSpace) {
+        self.stack.push(ns);
+        self.frames.push(self.next_frame);
+        self.next_frame += 1;
+    }
+
+    // les valeurs possédées par les variables du NameSpace dépilé sont libérées
+    pub fn pop(&mut self) -> Option<NameSpace> {
+        self.frames.pop();
+        let ns = self.stack.pop();
+        if let Some(ns) = &ns {
+            for value in ns.values() {
+                self.drop_value(&value);
+            }
+        }
+        ns
+    }
+
+    // vrai si `value` possède une ressource, qu'une copie partagerait
+    pub fn is_owned(&self, value: &Value) -> bool {
+        match value {
+            Value::Pointer(address) => matches!(self.ptr_kind(address), Some(PtrKind::Box | PtrKind::Rc)),
+            Value::String(..) | Value::Vec(..) => true,
+            Value::Array(values) | Value::Tuple(values) => values.iter().any(|value| self.is_owned(value)),
+            _ => false,
+        }
+    }
+
+    // libère récursivement les Box, les String et les Vec possédés par
+    // `value`, et les Rc dont c'était la dernière référence forte
+    pub fn drop_value(&mut self, value: &Value) {
+        if let Value::Array(values) | Value::Tuple(values) = value {
+            for value in values {
+                self.drop_value(value);
+            }
+        }
+        if let Value::Pointer(Address::HeapAddress(i)) | Value::String(Address::HeapAddress(i))
+            | Value::Vec(Address::HeapAddress(i), _) = value {
+            let release = match self.heap.kind(*i) {
+                Some(PtrKind::Box) => true,
+                Some(PtrKind::Rc) => matches!(self.heap.decrement(*i), Ok(0)),
+                _ => false,
+            };
+            if release {
+                if let Some(inner) = self.heap.get(*i).ok().and_then(MemoryCell::owned_value) {
+                    self.drop_value(&inner);
+                }
+                let _ = self.heap.free(*i);
+            }
+        }
+    }
+
+    // contenu du tampon d'une String ou d'un Vec ; les autres valeurs sont
+    // leur propre contenu
+    pub fn read_buffer(&self, value: Value) -> Result<Value, EvalError> {
+        match value {
+            Value::String(buffer) | Value::Vec(buffer, _) => self.read_at(&buffer),
+            value => Ok(value),
+        }
+    }
+
+    // valeur affichée ou comparée : les String et les Vec, même imbriqués,
+    // sont remplacés par le contenu de leur tampon
+    pub fn contents(&self, value: &Value) -> Value {
+        match value {
+            Value::String(buffer) | Value::Vec(buffer, _) => match self.read_at(buffer) {
+                Ok(content) => self.contents(&content),
+                Err(_) => value.clone(),
+            },
+            Value::Array(values) => Value::Array(values.iter().map(|value| self.contents(value)).collect()),
+            Value::Tuple(values) => Value::Tuple(values.iter().map(|value| self.contents(value)).collect()),
+            value => value.clone(),
+        }
+    }
+
+    pub fn rc_clone(&mut self, address: &Address) -> Result<(), EvalError> {
+        match address {
+            Address::HeapAddress(i) => self.heap.increment(*i).map(|_| ()),
+            _ => Err(EvalError::NonAllocatedCell(None)),
+        }
+    }
+
+    // nombre de références fortes vers la cellule pointée par un Rc
+    pub fn strong_count(&self, value: &Value) -> Option<usize> {
+        match value {
+            Value::Pointer(Address::HeapAddress(i)) if self.heap.kind(*i) == Some(PtrKind::Rc) =>
+                self.heap.strong_count(*i).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn declare(&mut self, id: &Identifier, mutable: bool, value: &Value) -> Result<(), EvalError> {
+        self.stack.last_mut().unwrap().declare(id, MemoryCell::new(mutable, value.clone()))
+    }
+
+    pub fn find(&self, id: &Identifier) -> Result<Value, EvalError> {
+        for ns in self.stack.iter().rev() {
+            if ns.contains_key(id) {
+                return ns.find(id);
+            }
+        }
+        Err(EvalError::Undefined(id.clone()))
+    }
+
+    pub fn get_address(&self, id: &Identifier) -> Result<Address, EvalError> {
+        for (ns, frame) in self.stack.iter().zip(&self.frames).rev() {
+            if ns.contains_key(id) {
+                return Ok(Address::StackAddress(*frame, id.clone()));
+            }
+        }
+        Err(EvalError::Undefined(id.clone()))
+    }
+
+    fn frame(&self, frame: usize) -> Result<&NameSpace, EvalError> {
+        match self.frames.iter().position(|f| *f == frame) {
+            Some(depth) => Ok(&self.stack[depth]),
+            None => Err(EvalError::DanglingPointer(None)),
+        }
+    }
+
+    fn frame_mut(&mut self, frame: usize) -> Result<&mut NameSpace, EvalError> {
+        match self.frames.iter().position(|f| *f == frame) {
+            Some(depth) => Ok(&mut self.stack[depth]),
+            None => Err(EvalError::DanglingPointer(None)),
+        }
+    }
+
+    pub fn malloc(&mut self, kind: PtrKind, value: Value) -> Address {
+        self.heap.malloc(kind, value)
+    }
+
+    pub fn malloc_uninitialized(&mut self, kind: PtrKind) -> Address {
+        self.heap.malloc_uninitialized(kind)
+    }
+
+    pub fn ptr_kind(&self, address: &Address) -> Option<PtrKind> {
+        match address {
+            Address::HeapAddress(i) => self.heap.kind(*i),
+            _ => None,
+        }
+    }
+
+    fn cell(&self, address: &Address) -> Result<&MemoryCell, EvalError> {
+        match address {
+            Address::StackAddress(frame, id) => self.frame(*frame)?.cell(id),
+            Address::HeapAddress(i) => self.heap.get(*i),
+            Address::ElementAddress(..) => Err(EvalError::NonAllocatedCell(None)),
+        }
+    }
+
+    fn cell_mut(&mut self, address: &Address) -> Result<&mut MemoryCell, EvalError> {
+        match address {
+            Address::StackAddress(frame, id) => self.frame_mut(*frame)?.cell_mut(id),
+            Address::HeapAddress(i) => self.heap.get_mut(*i),
+            Address::ElementAddress(..) => Err(EvalError::NonAllocatedCell(None)),
+        }
+    }
+
+    // cellule qui contient l'élément désigné par `address`, et chemin de
+    // l'élément dans sa valeur ; les éléments d'un Vec sont dans son tampon
+    fn locate(&self, address: &Address) -> Result<(Address, Vec<usize>), EvalError> {
+        match address {
+            Address::ElementAddress(container, i) => {
+                let (cell, mut path) = self.locate(container)?;
+                if let Value::Vec(buffer, _) = self.cell(&cell)?.field(&path)? {
+                    return Ok((buffer.clone(), vec![*i]));
+                }
+                path.push(*i);
+                Ok((cell, path))
+            },
+            address => Ok((address.clone(), vec![])),
+        }
+    }
+
+    pub fn read_at(&self, address: &Address) -> Result<Value, EvalError> {
+        match address {
+            Address::StackAddress(frame, id) => self.frame(*frame)?.find(id),
+            Address::HeapAddress(i) => self.heap.read(*i),
+            Address::ElementAddress(..) => {
+                let (cell, path) = self.locate(address)?;
+                self.cell(&cell)?.get_field(&path)
+            },
+        }
+    }
+
+    // valeur à l'adresse, dont des champs ont pu être déplacés : seule sa
+    // forme peut servir, pour atteindre ses autres champs
+    pub fn peek_at(&self, address: &Address) -> Result<Value, EvalError> {
+        let (cell, path) = self.locate(address)?;
+        self.cell(&cell)?.field(&path).cloned()
+    }
+
+    // un élément est écrit dans la cellule qui le contient ; un élément de
+    // Vec est écrit dans son tampon, mais le Vec lui-même doit pouvoir être
+    // réécrit
+    pub fn write_at(&mut self, address: &Address, value: Value) -> Result<(), EvalError> {
+        match address {
+            Address::StackAddress(frame, id) => self.frame_mut(*frame)?.set(id, value),
+            Address::HeapAddress(i) => self.heap.write(*i, value),
+            Address::ElementAddress(container, i) => {
+                let (cell, mut path) = self.locate(container)?;
+                if let Value::Vec(buffer, element) = self.cell(&cell)?.field(&path)?.clone() {
+                    self.write_at(container, Value::Vec(buffer.clone(), element))?;
+                    return self.cell_mut(&buffer)?.set_field(&[*i], value);
+                }
+                path.push(*i);
+                self.cell_mut(&cell)?.set_field(&path, value)
+            },
+        }
+    }
+
+    // un champ de tuple peut être déplacé seul
+    pub fn move_out(&mut self, address: &Address) -> Result<(), EvalError> {
+        match address {
+            Address::StackAddress(frame, id) => self.frame_mut(*frame)?.move_out(id),
+            Address::HeapAddress(i) => self.heap.move_out(*i),
+            Address::ElementAddress(..) => {
+                let (cell, path) = self.locate(address)?;
+                self.cell_mut(&cell)?.move_field(&path)
+            },
+        }
+    }
+
+    pub fn free(&mut self, address: &Address) -> Result<(), EvalError> {
+        match address {
+            Address::HeapAddress(i) => self.heap.free(*i).map_err(|err| match err {
+                EvalError::UseAfterFree(e) => EvalError::DoubleFree(e),
+                err => err,
+            }),
+            _ => Err(EvalError::NonAllocatedCell(None)),
+        }
+    }
+}
@@ -1,10 +1,15 @@
 use std::fmt::{self, Display};
 
+use crate::identifier::Identifier;
+
 #[derive(Debug)]
 #[allow(dead_code)]
 pub enum ParseError {
     CannotParse,
     SyntaxNotSupported,
+    InvalidFormat(String),
+    BoundTwice(Identifier),
+    LiteralTooLarge(String),
 }
 
 impl Display for ParseError {
@@ -13,6 +18,9 @@ impl Display for ParseError {
         match self {
             CannotParse => write!(f, "Cannot parse"),
             SyntaxNotSupported => write!(f, "Syntax not supported"),
+            InvalidFormat(message) => write!(f, "Invalid format string: {}", message),
+            BoundTwice(id) => write!(f, "`{}` is bound more than once in the same pattern", id),
+            LiteralTooLarge(digits) => write!(f, "integer literal `{}` is too large", digits),
         }
     }
 }
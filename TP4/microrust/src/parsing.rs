@@ -4,9 +4,12 @@ extern crate lazy_static;
 
 pub mod binop;
 pub mod expression;
+pub mod format;
 pub mod instruction;
 pub mod leftexpression;
+pub mod pattern;
 pub mod ptr_kind;
+pub mod unop;
 pub mod utils;
 pub mod parsedvalue;
 
@@ -12,7 +12,12 @@ pub enum Binop {
     Eq,
     Neq,
     And,
-    Or
+    Or,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
 }
 
 use std::fmt::{self, Display};
@@ -34,6 +39,11 @@ impl Display for Binop {
             Neq => write!(f, "!="),
             And => write!(f, "&&"),
             Or => write!(f, "||"),
+            BitAnd => write!(f, "&"),
+            BitOr => write!(f, "|"),
+            BitXor => write!(f, "^"),
+            Shl => write!(f, "<<"),
+            Shr => write!(f, ">>"),
         }
     }
 }
@@ -48,7 +58,7 @@ use crate::parser::{Parse, ParseError};
 impl Parse for Binop {
 
     fn parse(input: &str) -> Result<Self, ParseError> {
-        match PestParser::parse(Rule::start_rule_binop, &input) {
+        match PestParser::parse(Rule::start_rule_binop, input) {
             Ok(mut pairs) => {
                 let first_rule = pairs.next().unwrap();
                 match first_rule.as_rule() {
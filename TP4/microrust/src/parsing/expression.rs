@@ -1,13 +1,34 @@
 use super::leftexpression::LeftExpression;
 use super::binop::Binop;
+use super::unop::Unop;
 use super::ptr_kind::PtrKind;
 use super::parsedvalue::ParsedValue;
+use crate::r#type::Type;
 
 #[derive(Debug)]
 pub enum Expression {
     Const(ParsedValue),
     NewPtr(PtrKind, Box<Expression>),
+    RcClone(LeftExpression),
+    RawAlloc,
     ValueAt(LeftExpression),
+    AmpersAnd(LeftExpression),
+    AmpersAndMut(LeftExpression),
+    UnOp(Unop, Box<Expression>),
+    Cast(Box<Expression>, Type),
+    Len(Box<Expression>),
+    StringFrom(Box<Expression>),
+    Array(Vec<Expression>),
+    // `[e; n]`
+    Repeat(Box<Expression>, Box<Expression>),
+    // `vec!` d'un tableau ou d'une répétition
+    VecFrom(Box<Expression>),
+    Index(Box<Expression>, Box<Expression>),
+    Push(Box<Expression>, Box<Expression>),
+    Pop(Box<Expression>),
+    // `()` est le tuple vide
+    Tuple(Vec<Expression>),
+    Field(Box<Expression>, usize),
     BinOp(Box<Expression>, Binop, Box<Expression>),
     Conditional{
         cond: Box<Expression>,
@@ -25,7 +46,23 @@ impl Display for Expression {
         match self {
             Const(i) => write!(f, "{}", i),
             NewPtr(boxkind, expr) => write!(f, "{}::new({})", boxkind, expr),
+            RcClone(lexpr) => write!(f, "Rc::clone(&{})", lexpr),
+            RawAlloc => write!(f, "RawPtr::new()"),
             ValueAt(lexpr) => write!(f, "{}", lexpr),
+            AmpersAnd(lexpr) => write!(f, "&{}", lexpr),
+            AmpersAndMut(lexpr) => write!(f, "&mut {}", lexpr),
+            UnOp(op, e) => write!(f, "{}{}", op, e),
+            Cast(e, t) => write!(f, "({} as {})", e, t),
+            Len(e) => write!(f, "({}).len()", e),
+            StringFrom(e) => write!(f, "String::from({})", e),
+            Array(es) => write!(f, "[{}]", es.iter().map(|e| e.to_string()).collect::<Vec<_>>().join(", ")),
+            Repeat(e, n) => write!(f, "[{}; {}]", e, n),
+            VecFrom(e) => write!(f, "vec!{}", e),
+            Index(e, i) => write!(f, "({})[{}]", e, i),
+            Push(e, x) => write!(f, "({}).push({})", e, x),
+            Pop(e) => write!(f, "({}).pop()", e),
+            Tuple(es) => write!(f, "{}", crate::r#type::tuple(es)),
+            Field(e, i) => write!(f, "({}).{}", e, i),
             BinOp(lhs, op, rhs) => write!(f, "({} {} {})", lhs, op, rhs),
             Conditional { cond, cond_true, cond_false } => 
                 write!(f, "({}) ? {}  : {} ", cond, cond_true, cond_false)
@@ -36,18 +73,19 @@ impl Display for Expression {
 
 use pest::Parser;
 
-use super::utils::{ PestParser, Rule, parse_expr};
+use super::utils::{ PestParser, Rule, check_literals, parse_expr};
 use crate::parser::{ Parse, ParseError};
 
 
 impl Parse for Expression {
 
     fn parse(input: &str) -> Result<Self, ParseError> {
-        match PestParser::parse(Rule::start_rule_expr, &input) {
+        match PestParser::parse(Rule::start_rule_expr, input) {
             Ok(mut pairs) => {
                 let first_rule = pairs.next().unwrap();
                 match first_rule.as_rule() {
                     Rule::expr => {
+                        check_literals(&first_rule)?;
                         Ok(parse_expr(first_rule.into_inner()))
                     }
                     _ => { panic!("the grammar is not as expected") }
@@ -0,0 +1,183 @@
+use std::fmt::{self, Display};
+
+use crate::identifier::Identifier;
+use crate::parser::ParseError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+// `{:spec}` : remplissage, alignement, `0`, largeur, précision et `?`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spec {
+    pub fill: char,
+    pub align: Option<Align>,
+    // les nombres sont complétés par des zéros après leur signe
+    pub zero: bool,
+    pub width: Option<usize>,
+    pub precision: Option<usize>,
+    pub debug: bool,
+}
+
+// un morceau de chaîne de format : du texte, ou l'indice de l'argument à afficher
+#[derive(Debug, Clone, PartialEq)]
+pub enum Piece {
+    Text(String),
+    Arg(usize, Spec),
+}
+
+impl Display for Spec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(align) = self.align {
+            let align = match align {
+                Align::Left => '<',
+                Align::Right => '>',
+                Align::Center => '^',
+            };
+            write!(f, "{}{}", self.fill, align)?;
+        }
+        if self.zero {
+            write!(f, "0")?;
+        }
+        if let Some(width) = self.width {
+            write!(f, "{}", width)?;
+        }
+        if let Some(precision) = self.precision {
+            write!(f, ".{}", precision)?;
+        }
+        if self.debug {
+            write!(f, "?")?;
+        }
+        Ok(())
+    }
+}
+
+// chaîne de format équivalente, où chaque argument est désigné par son indice
+pub fn format_string(pieces: &[Piece]) -> String {
+    pieces.iter()
+        .map(|piece| match piece {
+            Piece::Text(text) => text.replace('{', "{{").replace('}', "}}"),
+            Piece::Arg(i, spec) => {
+                let spec = spec.to_string();
+                if spec.is_empty() { format!("{{{}}}", i) } else { format!("{{{}:{}}}", i, spec) }
+            },
+        })
+        .collect()
+}
+
+fn invalid(message: String) -> ParseError {
+    ParseError::InvalidFormat(message)
+}
+
+fn parse_spec(spec: &str) -> Result<Spec, ParseError> {
+    let mut res = Spec { fill: ' ', align: None, zero: false, width: None, precision: None, debug: false };
+    let mut chars: Vec<char> = spec.chars().collect();
+    let align = |c: char| match c {
+        '<' => Some(Align::Left),
+        '>' => Some(Align::Right),
+        '^' => Some(Align::Center),
+        _ => None,
+    };
+    if let Some(a) = chars.get(1).copied().and_then(align) {
+        res.fill = chars[0];
+        res.align = Some(a);
+        chars.drain(..2);
+    } else if let Some(a) = chars.first().copied().and_then(align) {
+        res.align = Some(a);
+        chars.remove(0);
+    }
+    let number = |chars: &mut Vec<char>| {
+        let digits: String = chars.iter().take_while(|c| c.is_ascii_digit()).collect();
+        chars.drain(..digits.len());
+        digits.parse::<usize>().ok()
+    };
+    if chars.first() == Some(&'0') {
+        chars.remove(0);
+        res.zero = true;
+    }
+    res.width = number(&mut chars);
+    if chars.first() == Some(&'.') {
+        chars.remove(0);
+        res.precision = Some(number(&mut chars).ok_or_else(|| invalid(format!("missing precision in `{{:{}}}`", spec)))?);
+    }
+    if chars.first() == Some(&'?') {
+        chars.remove(0);
+        res.debug = true;
+    }
+    if !chars.is_empty() {
+        return Err(invalid(format!("unknown format spec `{{:{}}}`", spec)));
+    }
+    Ok(res)
+}
+
+// découpe `format` en morceaux ; les arguments sont, dans l'ordre, les
+// `positional` arguments positionnels, les arguments nommés `named`, puis les
+// variables capturées par un `{nom}` qui n'est pas un argument nommé,
+// ajoutées à `named` ; chaque argument donné doit être utilisé
+pub fn parse_format(format: &str, positional: usize, named: &mut Vec<Identifier>) -> Result<Vec<Piece>, ParseError> {
+    let given = positional + named.len();
+    let mut used = vec![false; given];
+    let mut pieces = vec![];
+    let mut text = String::new();
+    let mut next = 0;
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                text.push(c);
+            },
+            ('}', _) => return Err(invalid("unmatched `}` in format string".to_string())),
+            ('{', _) => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => return Err(invalid("unterminated `{` in format string".to_string())),
+                    }
+                }
+                let (arg, spec) = placeholder.split_once(':').unwrap_or((&placeholder, ""));
+                let i = if arg.is_empty() {
+                    next += 1;
+                    next - 1
+                } else if let Ok(i) = arg.parse::<usize>() {
+                    i
+                } else if !arg.starts_with(|c: char| c.is_alphabetic() || c == '_')
+                    || !arg.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    return Err(invalid(format!("invalid argument name `{}`", arg)));
+                } else {
+                    let id = Identifier::from(arg);
+                    match named.iter().position(|named| *named == id) {
+                        Some(i) => positional + i,
+                        None => {
+                            named.push(id);
+                            positional + named.len() - 1
+                        },
+                    }
+                };
+                if i < given {
+                    used[i] = true;
+                } else if arg.is_empty() || arg.parse::<usize>().is_ok() {
+                    return Err(invalid(format!("invalid reference to positional argument {} ({} arguments)", i, given)));
+                }
+                if !text.is_empty() {
+                    pieces.push(Piece::Text(std::mem::take(&mut text)));
+                }
+                pieces.push(Piece::Arg(i, parse_spec(spec)?));
+            },
+            _ => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        pieces.push(Piece::Text(text));
+    }
+    match used.iter().position(|used| !used) {
+        Some(i) if i < positional => Err(invalid(format!("argument {} never used", i))),
+        Some(i) => Err(invalid(format!("named argument `{}` never used", named[i - positional]))),
+        None => Ok(pieces),
+    }
+}
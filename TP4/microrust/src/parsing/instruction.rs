@@ -1,11 +1,13 @@
 use crate::parsing::expression::Expression;
 use crate::parsing::leftexpression::LeftExpression;
-use crate::identifier::Identifier;
+use crate::parsing::format::{self, Piece};
+use crate::parsing::pattern::Pattern;
+use crate::r#type::Type;
 
 #[derive(Debug)]
 pub enum Instruction {
     Expr(Expression),
-    Let{id:Identifier, mutable:bool, expr:Expression},
+    Let{pattern:Pattern, ty:Option<Type>, expr:Expression},
     Block(Vec<Instruction>),
     IfElse{
         cond: Expression,
@@ -15,7 +17,9 @@ pub enum Instruction {
     WriteAt(LeftExpression, Expression),
     While(Expression, Box<Instruction>),
     Free(LeftExpression),
-    Drop(LeftExpression)
+    Drop(LeftExpression),
+    // `print!` ou `println!`, les `Piece::Arg` désignant un élément de `args`
+    Print{newline: bool, pieces: Vec<Piece>, args: Vec<Expression>},
 } 
 
 use std::fmt::Display;
@@ -24,15 +28,12 @@ impl Display for Instruction {
         use Instruction::*;
         match self {
             Expr(expr) => write!(f, "{}", expr),
-            Let{id, mutable, expr} => {
-                if *mutable {
-                    write!(f, "let mut {} = {}", id, expr)
-                } else {
-                    write!(f, "let {} = {}", id, expr)
-                }
+            Let{pattern, ty, expr} => {
+                let ty = ty.as_ref().map(|t| format!(": {}", t)).unwrap_or_default();
+                write!(f, "let {}{} = {}", pattern, ty, expr)
             },
             Block(instrs) => {
-                write!(f, "{{{}}}", instrs.into_iter().map(|x| x.to_string()).collect::<Vec<_>>().join(";"))
+                write!(f, "{{{}}}", instrs.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(";"))
             },
             IfElse{cond, cond_true, cond_false} => {
                 write!(f, "if {} {} else {}", cond, cond_true, cond_false)
@@ -48,6 +49,13 @@ impl Display for Instruction {
             },
             Drop(lexpr) => {
                 write!(f, "drop {}", lexpr)
+            },
+            Print{newline, pieces, args} => {
+                write!(f, "{}!({:?}", if *newline { "println" } else { "print" }, format::format_string(pieces))?;
+                for arg in args {
+                    write!(f, ", {}", arg)?;
+                }
+                write!(f, ")")
             }
         }
     }
@@ -56,15 +64,16 @@ impl Display for Instruction {
 
 use pest::Parser;
 use crate::parser::{ParseError, Parse};
-use super::utils::{PestParser, Rule, parse_instr};
+use super::utils::{PestParser, Rule, check_literals, parse_instr};
 
 impl Parse for Instruction {
     fn parse(input: &str) -> Result<Self, ParseError> {
-        match PestParser::parse(Rule::start_rule_instr, &input) {
+        match PestParser::parse(Rule::start_rule_instr, input) {
             Ok(mut pairs) => {
                 let first_rule = pairs.next().unwrap();
                 match first_rule.as_rule() {
                     Rule::instr => {
+                        check_literals(&first_rule)?;
                         parse_instr(&mut first_rule.into_inner())
                     }
                     _ => { panic!("the grammar is not as expected") }
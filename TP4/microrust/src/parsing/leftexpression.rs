@@ -1,11 +1,14 @@
 use std::fmt::{self, Display};
 
 use crate::identifier::Identifier;
+use crate::parsing::expression::Expression;
 
 #[derive(Debug)]
 pub enum LeftExpression {
     Identifier(Identifier),
     Star(Box<LeftExpression>),
+    Index(Box<LeftExpression>, Box<Expression>),
+    Field(Box<LeftExpression>, usize),
 }
 
 impl Display for LeftExpression {
@@ -14,6 +17,10 @@ impl Display for LeftExpression {
         match self {
             Identifier(id) => {write!(f, "{}", id)? },
             Star(lexpr) => {write!(f, "*{}", lexpr)?},
+            Index(lexpr, i) if matches!(**lexpr, Star(_)) => {write!(f, "({})[{}]", lexpr, i)?},
+            Index(lexpr, i) => {write!(f, "{}[{}]", lexpr, i)?},
+            Field(lexpr, i) if matches!(**lexpr, Star(_)) => {write!(f, "({}).{}", lexpr, i)?},
+            Field(lexpr, i) => {write!(f, "{}.{}", lexpr, i)?},
         };
         Ok(())
     }
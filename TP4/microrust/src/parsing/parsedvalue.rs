@@ -1,12 +1,15 @@
 use std::fmt;
 
 use crate::memory::Address;
+use crate::r#type::Type;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ParsedValue {
-    Integer(isize),
+    // `Type::IntLiteral` si le littéral n'a pas de suffixe
+    Integer(i128, Type),
     Boolean(bool),
-    Unit,
+    Char(char),
+    Str(String),
     Pointer(Address),
 }
 
@@ -14,9 +17,12 @@ impl fmt::Display for ParsedValue {
 
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ParsedValue::Integer(i) => write!(f, "{}", i),
+            ParsedValue::Integer(i, Type::IntLiteral) => write!(f, "{}", i),
+            ParsedValue::Integer(i, t) => write!(f, "{}{}", i, t),
             ParsedValue::Boolean(b) => write!(f, "{}", b),
-            ParsedValue::Unit => write!(f, "()"),
+            // écrits avec leurs échappements, pour pouvoir être relus
+            ParsedValue::Char(c) => write!(f, "{:?}", c),
+            ParsedValue::Str(s) => write!(f, "{:?}", s),
             ParsedValue::Pointer(a) => write!(f, "{:?}", a),
         }
     }
@@ -0,0 +1,31 @@
+use std::fmt::{self, Display};
+
+use crate::identifier::Identifier;
+use crate::r#type;
+
+// motif à gauche d'un `let` : une variable, ou un tuple de motifs
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    Identifier{id: Identifier, mutable: bool},
+    Tuple(Vec<Pattern>),
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pattern::Identifier{id, mutable: true} => write!(f, "mut {}", id),
+            Pattern::Identifier{id, mutable: false} => write!(f, "{}", id),
+            Pattern::Tuple(patterns) => write!(f, "{}", r#type::tuple(patterns)),
+        }
+    }
+}
+
+impl Pattern {
+    // variables liées par le motif, de gauche à droite
+    pub fn identifiers(&self) -> Vec<&Identifier> {
+        match self {
+            Pattern::Identifier{id, ..} => vec![id],
+            Pattern::Tuple(patterns) => patterns.iter().flat_map(Pattern::identifiers).collect(),
+        }
+    }
+}
@@ -0,0 +1,18 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unop {
+    Neg,
+    Not,
+}
+
+
+use std::fmt::Display;
+
+impl Display for Unop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use Unop::*;
+        match self {
+            Neg => write!(f, "-"),
+            Not => write!(f, "!"),
+        }
+    }
+}
@@ -10,12 +10,16 @@ pub struct PestParser;
 use super::instruction::Instruction;
 use super::expression::Expression;
 use super::leftexpression::LeftExpression;
+use super::pattern::Pattern;
 use super::binop::Binop;
+use super::unop::Unop;
 use super::ptr_kind::PtrKind;
 use super::parsedvalue::ParsedValue;
+use super::format::parse_format;
 
 use crate::identifier::Identifier;
 use crate::parser::ParseError;
+use crate::r#type::Type;
 
 lazy_static::lazy_static! {
     static ref PRATT_PARSER: PrattParser<Rule> = {
@@ -27,20 +31,33 @@ lazy_static::lazy_static! {
             // Addition and subtract have equal precedence
             .op(Op::infix(AND, Left) | Op::infix(OR, Left))
             .op(Op::infix(EQQUALS, Left) | Op::infix(NEQ, Left) | Op::infix(GEQ, Left) | Op::infix(LEQ, Left) | Op::infix(GREATER, Left) | Op::infix(LOWER, Left))
+            // bitwise operators bind tighter than comparisons, as in Rust
+            .op(Op::infix(BITOR, Left))
+            .op(Op::infix(BITXOR, Left))
+            .op(Op::infix(BITAND, Left))
+            .op(Op::infix(SHL, Left) | Op::infix(SHR, Left))
             .op(Op::infix(ADD, Left) | Op::infix(SUBTRACT, Left))
             .op(Op::infix(MULTIPLY, Left) | Op::infix(DIVIDE, Left) | Op::infix(MODULO, Left))
-//            .op(Op::prefix(unary_minus))
+            // `as` binds tighter than binary operators but looser than unary ones
+            .op(Op::postfix(cast))
+            .op(Op::prefix(NEG) | Op::prefix(NOT))
+            .op(Op::postfix(len_call) | Op::postfix(push_call) | Op::postfix(pop_call) | Op::postfix(index) | Op::postfix(field))
     };
 }
 
 pub fn parse_lexpr(mut pairs: Pairs<Rule>) -> LeftExpression {
     let first_rule = pairs.next().unwrap();
-    match first_rule.as_rule() {
+    let lexpr = match first_rule.as_rule() {
         Rule::identifier => LeftExpression::Identifier(Identifier::from(first_rule.as_str())),
         Rule::deref => LeftExpression::Star(Box::new(parse_lexpr(first_rule.into_inner()))),
         Rule::lexpr => parse_lexpr(first_rule.into_inner()),
         _ => unreachable!()
-    }
+    };
+    // les indices et les champs qui suivent : `a[i].0[j]`
+    pairs.fold(lexpr, |lexpr, rule| match rule.as_rule() {
+        Rule::field => LeftExpression::Field(Box::new(lexpr), parse_field(rule)),
+        _ => LeftExpression::Index(Box::new(lexpr), Box::new(parse_expr(rule.into_inner().next().unwrap().into_inner()))),
+    })
 }
 
 
@@ -64,6 +81,11 @@ pub fn parse_binop_rule(first_rule: Pair<'_, Rule>) -> Binop {
         Rule::LOWER => Binop::Lt,
         Rule::AND => Binop::And,
         Rule::OR => Binop::Or,
+        Rule::BITAND => Binop::BitAnd,
+        Rule::BITOR => Binop::BitOr,
+        Rule::BITXOR => Binop::BitXor,
+        Rule::SHL => Binop::Shl,
+        Rule::SHR => Binop::Shr,
         _ => unreachable!()
     }
 }
@@ -81,10 +103,97 @@ pub fn parse_boxkind_rule(first_rule: Pair<'_, Rule>) -> PtrKind {
     }
 }
 
+// numéro d'un champ `.i` ; un numéro trop grand ne désigne aucun champ
+fn parse_field(rule: Pair<'_, Rule>) -> usize {
+    rule.into_inner().as_str().parse().unwrap_or(usize::MAX)
+}
+
+// `int_type`, `tuple_type` ou `type_expr`
+pub fn parse_type(rule: Pair<'_, Rule>) -> Type {
+    if rule.as_rule() == Rule::tuple_type {
+        return Type::Tuple(rule.into_inner().map(parse_type).collect());
+    }
+    if let Some(inner) = rule.clone().into_inner().next() {
+        return parse_type(inner);
+    }
+    match rule.as_str() {
+        "i8" => Type::I8,
+        "i16" => Type::I16,
+        "i32" => Type::I32,
+        "i64" => Type::I64,
+        "isize" => Type::Int,
+        "u8" => Type::U8,
+        "u16" => Type::U16,
+        "u32" => Type::U32,
+        "u64" => Type::U64,
+        "usize" => Type::Usize,
+        "bool" => Type::Bool,
+        "char" => Type::Char,
+        "&str" => Type::Str,
+        "String" => Type::String,
+        s => unreachable!("parse_type expected a type, found {}", s),
+    }
+}
+
+// contenu d'un littéral `string_inner` ou `char_inner`, échappements résolus
+fn unescape(literal: &str) -> String {
+    let mut res = String::new();
+    let mut chars = literal.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            res.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => res.push('\n'),
+            Some('r') => res.push('\r'),
+            Some('t') => res.push('\t'),
+            Some('0') => res.push('\0'),
+            Some('u') => {
+                let code: String = chars.by_ref().skip(1).take_while(|c| *c != '}').collect();
+                let code = u32::from_str_radix(&code, 16).unwrap();
+                res.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+            },
+            Some(c) => res.push(c),
+            None => unreachable!("the grammar forbids a trailing backslash"),
+        }
+    }
+    res
+}
+
+// un entier littéral doit tenir dans un i128, vérifié avant de construire
+// l'AST pour que `parse_expr` n'échoue pas
+pub fn check_literals(pair: &Pair<Rule>) -> Result<(), ParseError> {
+    for integer in pair.clone().into_inner().flatten().filter(|pair| pair.as_rule() == Rule::integer) {
+        let digits = integer.into_inner().next().unwrap().as_str();
+        if digits.parse::<i128>().is_err() {
+            return Err(ParseError::LiteralTooLarge(digits.to_string()));
+        }
+    }
+    Ok(())
+}
+
 pub fn parse_expr(pairs: Pairs<Rule>) -> Expression {
     PRATT_PARSER
         .map_primary(|primary| match primary.as_rule() {
-            Rule::integer => Expression::Const(ParsedValue::Integer(primary.as_str().parse::<isize>().unwrap())),
+            Rule::integer => {
+                let mut rules = primary.into_inner();
+                let i = rules.next().unwrap().as_str().parse::<i128>().expect("literals are checked by check_literals");
+                let t = rules.next().map(parse_type).unwrap_or(Type::IntLiteral);
+                Expression::Const(ParsedValue::Integer(i, t))
+            },
+            Rule::string => Expression::Const(ParsedValue::Str(unescape(primary.into_inner().as_str()))),
+            Rule::char => Expression::Const(ParsedValue::Char(unescape(primary.into_inner().as_str()).chars().next().unwrap())),
+            Rule::string_from => Expression::StringFrom(Box::new(parse_expr(primary.into_inner().next().unwrap().into_inner()))),
+            Rule::array_lit => Expression::Array(primary.into_inner().map(|e| parse_expr(e.into_inner())).collect()),
+            Rule::array_repeat => {
+                let mut rules = primary.into_inner();
+                let e = parse_expr(rules.next().unwrap().into_inner());
+                let n = parse_expr(rules.next().unwrap().into_inner());
+                Expression::Repeat(Box::new(e), Box::new(n))
+            },
+            Rule::vec_macro => Expression::VecFrom(Box::new(parse_expr(primary.into_inner()))),
+            Rule::vec_new => Expression::VecFrom(Box::new(Expression::Array(vec![]))),
             Rule::boolean => Expression::Const(ParsedValue::Boolean(match primary.as_str() {
                 "true" => true,
                 "false" => false,
@@ -98,6 +207,14 @@ pub fn parse_expr(pairs: Pairs<Rule>) -> Expression {
                 let expr = parse_expr(rules.next().unwrap().into_inner());
                 Expression::NewPtr(boxkind, Box::new(expr))
             },
+            Rule::rcclone => {
+                let mut rules = primary.into_inner();
+                rules.next(); // RC
+                Expression::RcClone(parse_lexpr(rules))
+            },
+            Rule::rawalloc => Expression::RawAlloc,
+            Rule::ampersand => Expression::AmpersAnd(parse_lexpr(primary.into_inner())),
+            Rule::ampersand_mut => Expression::AmpersAndMut(parse_lexpr(primary.into_inner())),
             Rule::atom => parse_expr(primary.into_inner()),
             Rule::conditional_expr => {
                 let mut rules = primary.into_inner();
@@ -106,9 +223,25 @@ pub fn parse_expr(pairs: Pairs<Rule>) -> Expression {
                 let cond_false = Box::new(parse_expr(rules.next().unwrap().into_inner()));
                 Expression::Conditional{cond, cond_true, cond_false}
             },
-            Rule::unit => Expression::Const(ParsedValue::Unit),
+            Rule::tuple => Expression::Tuple(primary.into_inner().map(|e| parse_expr(e.into_inner())).collect()),
             rule => unreachable!("parse_expr expected atom, found {:?}", rule),
         })
+        .map_prefix(|op, rhs| match (op.as_rule(), rhs) {
+            // un littéral signé précédé de `-` est un littéral négatif
+            (Rule::NEG, Expression::Const(ParsedValue::Integer(i, t))) if t.is_signed() => Expression::Const(ParsedValue::Integer(-i, t)),
+            (Rule::NEG, rhs) => Expression::UnOp(Unop::Neg, Box::new(rhs)),
+            (Rule::NOT, rhs) => Expression::UnOp(Unop::Not, Box::new(rhs)),
+            (rule, _) => unreachable!("parse_expr expected prefix operator, found {:?}", rule),
+        })
+        .map_postfix(|lhs, op| match op.as_rule() {
+            Rule::cast => Expression::Cast(Box::new(lhs), parse_type(op.into_inner().last().unwrap())),
+            Rule::len_call => Expression::Len(Box::new(lhs)),
+            Rule::push_call => Expression::Push(Box::new(lhs), Box::new(parse_expr(op.into_inner().next().unwrap().into_inner()))),
+            Rule::pop_call => Expression::Pop(Box::new(lhs)),
+            Rule::field => Expression::Field(Box::new(lhs), parse_field(op)),
+            Rule::index => Expression::Index(Box::new(lhs), Box::new(parse_expr(op.into_inner().next().unwrap().into_inner()))),
+            rule => unreachable!("parse_expr expected postfix operator, found {:?}", rule),
+        })
         .map_infix(|lhs, op, rhs| {
             let op2 = parse_binop_rule(op);
             Expression::BinOp(Box::new(lhs), op2, Box::new(rhs))
@@ -122,8 +255,8 @@ fn parse_block(pairs: &mut Pairs<Rule>) -> Result<Vec<Instruction>, ParseError>
     match first_rule.as_rule() {
         Rule::empty_block => { },
         Rule::non_empty_block => {
-            let mut rules = first_rule.into_inner();
-            while let Some(rule) = rules.next(){
+            let rules = first_rule.into_inner();
+            for rule in rules {
                 if rule.as_rule() == Rule::instr {
                     let instr = parse_instr(&mut rule.into_inner())?;
                     res.push(instr)
@@ -135,23 +268,38 @@ fn parse_block(pairs: &mut Pairs<Rule>) -> Result<Vec<Instruction>, ParseError>
     Ok(res)
 }
 
+// annotation facultative `: type` d'un `let`
+fn parse_pattern(rule: Pair<'_, Rule>) -> Pattern {
+    match rule.as_rule() {
+        Rule::pattern => parse_pattern(rule.into_inner().next().unwrap()),
+        Rule::identifier => Pattern::Identifier{id: Identifier::from(rule.as_str()), mutable: false},
+        Rule::mut_identifier => Pattern::Identifier{id: Identifier::from(rule.into_inner().as_str()), mutable: true},
+        Rule::tuple_pattern => Pattern::Tuple(rule.into_inner().map(parse_pattern).collect()),
+        _ => unreachable!("parse_pattern expected a pattern, found {:?}", rule),
+    }
+}
+
+fn parse_annotation(rules: &mut Pairs<Rule>) -> Option<Type> {
+    match rules.peek() {
+        Some(rule) if rule.as_rule() == Rule::type_expr => rules.next().map(parse_type),
+        _ => None,
+    }
+}
+
 pub fn parse_instr(pairs: &mut Pairs<Rule>) -> Result<Instruction, ParseError> {
     let first_rule = pairs.next().unwrap();
     match first_rule.as_rule() {
         Rule::expr => Ok(Instruction::Expr(parse_expr(first_rule.into_inner()))),
         Rule::let_equals => {
             let mut rules = first_rule.into_inner();
-            let id = rules.next().unwrap().as_span().as_str().to_string();
-            let id = Identifier::from(id.as_str());
-            let expr = rules.next().unwrap().into_inner();
-            Ok(Instruction::Let{id, mutable: false, expr: parse_expr(expr) })
-        },
-        Rule::let_mut_equals => {
-            let mut rules = first_rule.into_inner();
-            let id = rules.next().unwrap().as_span().as_str().to_string();
-            let id = Identifier::from(id.as_str());
+            let pattern = parse_pattern(rules.next().unwrap());
+            let ids = pattern.identifiers();
+            if let Some(id) = ids.iter().enumerate().find_map(|(i, id)| ids[..i].contains(id).then_some(*id)) {
+                return Err(ParseError::BoundTwice(id.clone()));
+            }
+            let ty = parse_annotation(&mut rules);
             let expr = rules.next().unwrap().into_inner();
-            Ok(Instruction::Let{id, mutable: true, expr: parse_expr(expr) })
+            Ok(Instruction::Let{pattern, ty, expr: parse_expr(expr) })
         },
         Rule::update_instr => {
             let mut rules = first_rule.into_inner();
@@ -183,6 +331,34 @@ pub fn parse_instr(pairs: &mut Pairs<Rule>) -> Result<Instruction, ParseError> {
             let lexpr = parse_lexpr(first_rule.into_inner());
             Ok(Instruction::Drop(lexpr))
         },
+        Rule::print_instr => {
+            let mut rules = first_rule.into_inner();
+            let newline = rules.next().unwrap().as_rule() == Rule::PRINTLN;
+            let format = unescape(rules.next().unwrap().into_inner().as_str());
+            let (mut args, mut named, mut named_args) = (vec![], vec![], vec![]);
+            for rule in rules {
+                match rule.as_rule() {
+                    Rule::named_arg => {
+                        let mut rules = rule.into_inner();
+                        let id = Identifier::from(rules.next().unwrap().as_str());
+                        if named.contains(&id) {
+                            return Err(ParseError::InvalidFormat(format!("duplicate argument named `{}`", id)));
+                        }
+                        named.push(id);
+                        named_args.push(parse_expr(rules.next().unwrap().into_inner()));
+                    },
+                    _ if !named.is_empty() =>
+                        return Err(ParseError::InvalidFormat("positional arguments cannot follow named arguments".to_string())),
+                    _ => args.push(parse_expr(rule.into_inner())),
+                }
+            }
+            let (positional, given) = (args.len(), named.len());
+            let pieces = parse_format(&format, positional, &mut named)?;
+            // les variables capturées par `{nom}` suivent les arguments donnés
+            args.extend(named_args);
+            args.extend(named.drain(given..).map(|id| Expression::ValueAt(LeftExpression::Identifier(id))));
+            Ok(Instruction::Print{newline, pieces, args})
+        },
         _ => unreachable!("parse_instr expected instr, found {:?}", first_rule),
     }
 }
\ No newline at end of file
@@ -0,0 +1,124 @@
+use crate::{
+    error::EvalError,
+    instruction::Instruction,
+    parser::{Parse, ParseError},
+    value::Value,
+    r#type::Type,
+    test_utils::{eval, new_stack},
+};
+
+#[test]
+fn test_division_by_zero() {
+    for wrapping in [false, true] {
+        let mut nss = new_stack();
+        nss.set_wrapping(wrapping);
+        assert!(eval(&mut nss, "let zero = 1 - 1").is_ok());
+        assert!(matches!(eval(&mut nss, "1 / zero"), Err(EvalError::DivisionByZero(_))));
+        assert!(matches!(eval(&mut nss, "1 % (zero * 2)"), Err(EvalError::DivisionByZero(_))));
+    }
+}
+
+#[test]
+fn test_checked_overflow() {
+    let mut nss = new_stack();
+    assert!(eval(&mut nss, "let max = 9223372036854775807").is_ok());
+    assert!(matches!(eval(&mut nss, "max + 1"), Err(EvalError::Overflow(_))));
+    assert!(matches!(eval(&mut nss, "max * 2"), Err(EvalError::Overflow(_))));
+    assert!(eval(&mut nss, "let min = 0 - max - 1").is_ok());
+    assert!(matches!(eval(&mut nss, "min - 1"), Err(EvalError::Overflow(_))));
+    assert!(matches!(eval(&mut nss, "min / (0 - 1)"), Err(EvalError::Overflow(_))));
+    assert_eq!(eval(&mut nss, "max - 1 + 1").unwrap(), Value::Integer(isize::MAX as i128, Type::Int));
+}
+
+#[test]
+fn test_wrapping_overflow() {
+    let mut nss = new_stack();
+    nss.set_wrapping(true);
+    assert!(eval(&mut nss, "let max = 9223372036854775807").is_ok());
+    assert_eq!(eval(&mut nss, "max + 1").unwrap(), Value::Integer(isize::MIN as i128, Type::Int));
+    assert_eq!(eval(&mut nss, "max * 2").unwrap(), Value::Integer(-2, Type::Int));
+    assert_eq!(eval(&mut nss, "(max + 1) / (0 - 1)").unwrap(), Value::Integer(isize::MIN as i128, Type::Int));
+}
+
+#[test]
+fn test_unary_operators() {
+    let mut nss = new_stack();
+    assert_eq!(eval(&mut nss, "1 - -2").unwrap(), Value::Integer(3, Type::Int));
+    assert_eq!(eval(&mut nss, "-(2 + 3) * 2").unwrap(), Value::Integer(-10, Type::Int));
+    assert_eq!(eval(&mut nss, "!true").unwrap(), Value::Boolean(false));
+    assert_eq!(eval(&mut nss, "!5").unwrap(), Value::Integer(-6, Type::Int));
+    assert!(eval(&mut nss, "let min = -9223372036854775807 - 1").is_ok());
+    assert!(matches!(eval(&mut nss, "-min"), Err(EvalError::Overflow(_))));
+    assert!(matches!(eval(&mut nss, "!()"), Err(EvalError::TypeMismatch{..})));
+}
+
+#[test]
+fn test_bitwise_operators() {
+    let mut nss = new_stack();
+    // `&`, `^`, `|` et les décalages sont plus prioritaires que les comparaisons
+    assert_eq!(eval(&mut nss, "6 & 3 == 2").unwrap(), Value::Boolean(true));
+    assert_eq!(eval(&mut nss, "5 ^ 1 | 8").unwrap(), Value::Integer(12, Type::Int));
+    assert_eq!(eval(&mut nss, "1 + 2 << 1").unwrap(), Value::Integer(6, Type::Int));
+    assert_eq!(eval(&mut nss, "-8 >> 1").unwrap(), Value::Integer(-4, Type::Int));
+    assert_eq!(eval(&mut nss, "true & false | true").unwrap(), Value::Boolean(true));
+    assert!(matches!(eval(&mut nss, "1 & true"), Err(EvalError::TypeMismatch{..})));
+    assert!(matches!(eval(&mut nss, "1 << 64"), Err(EvalError::Overflow(_))));
+    assert!(matches!(eval(&mut nss, "1 >> -1"), Err(EvalError::Overflow(_))));
+}
+
+#[test]
+fn test_fixed_width_integers() {
+    let mut nss = new_stack();
+    assert!(eval(&mut nss, "let x: u8 = 255").is_ok());
+    assert!(eval(&mut nss, "let y = 200u8").is_ok());
+    assert_eq!(eval(&mut nss, "x - y").unwrap(), Value::Integer(55, Type::U8));
+    assert!(matches!(eval(&mut nss, "x + 1"), Err(EvalError::Overflow(_))));
+    assert!(matches!(eval(&mut nss, "0u32 - 1"), Err(EvalError::Overflow(_))));
+    assert!(matches!(eval(&mut nss, "let z: i8 = 128"), Err(EvalError::Overflow(_))));
+    assert!(matches!(eval(&mut nss, "300u8"), Err(EvalError::Overflow(_))));
+    assert!(matches!(eval(&mut nss, "x + 1u16"), Err(EvalError::TypeMismatch{..})));
+    assert!(matches!(eval(&mut nss, "let b: bool = 1"), Err(EvalError::TypeMismatch{..})));
+    assert_eq!(eval(&mut nss, "!0u8").unwrap(), Value::Integer(255, Type::U8));
+    assert!(matches!(eval(&mut nss, "-5u8"), Err(EvalError::TypeMismatch{found: Type::U8, ..})));
+    assert_eq!(eval(&mut nss, "3u8 << 7").unwrap(), Value::Integer(128, Type::U8));
+    assert!(matches!(eval(&mut nss, "1u8 << 8"), Err(EvalError::Overflow(_))));
+    assert!(eval(&mut nss, "let max: u64 = 18446744073709551615").is_ok());
+    assert_eq!(eval(&mut nss, "max / 2 + 1 == 9223372036854775808").unwrap(), Value::Boolean(true));
+    assert!(eval(&mut nss, "let mut n: i16 = 0").is_ok());
+    assert!(eval(&mut nss, "n = 1000").is_ok());
+    assert_eq!(eval(&mut nss, "n").unwrap(), Value::Integer(1000, Type::I16));
+}
+
+#[test]
+fn test_wrapping_fixed_width() {
+    let mut nss = new_stack();
+    nss.set_wrapping(true);
+    assert!(eval(&mut nss, "let y = 1usize").is_ok());
+    assert_eq!(eval(&mut nss, "255u8 + 1").unwrap(), Value::Integer(0, Type::U8));
+    assert_eq!(eval(&mut nss, "0usize - 1").unwrap(), Value::Integer(usize::MAX as i128, Type::Usize));
+    assert_eq!(eval(&mut nss, "-128i8 / -1").unwrap(), Value::Integer(-128, Type::I8));
+    assert_eq!(eval(&mut nss, "1i32 << 33").unwrap(), Value::Integer(2, Type::I32));
+    assert!(matches!(eval(&mut nss, "-y"), Err(EvalError::TypeMismatch{..})));
+}
+
+#[test]
+fn test_casts() {
+    let mut nss = new_stack();
+    assert_eq!(eval(&mut nss, "300 as u8").unwrap(), Value::Integer(44, Type::U8));
+    assert_eq!(eval(&mut nss, "-1 as u8").unwrap(), Value::Integer(255, Type::U8));
+    assert_eq!(eval(&mut nss, "255u8 as i8").unwrap(), Value::Integer(-1, Type::I8));
+    assert_eq!(eval(&mut nss, "-1i8 as u32").unwrap(), Value::Integer(u32::MAX as i128, Type::U32));
+    assert_eq!(eval(&mut nss, "true as u8 + 1").unwrap(), Value::Integer(2, Type::U8));
+    assert!(eval(&mut nss, "let x: u8 = 255").is_ok());
+    assert_eq!(eval(&mut nss, "x as u16 + 1").unwrap(), Value::Integer(256, Type::U16));
+    assert!(matches!(eval(&mut nss, "() as i32"), Err(EvalError::TypeMismatch{..})));
+}
+
+#[test]
+fn test_literal_types() {
+    let mut nss = new_stack();
+    assert!(matches!(Instruction::parse("1000000000000000000000000000000000000000 + 1"), Err(ParseError::LiteralTooLarge(_))));
+    assert_eq!(eval(&mut nss, "let x: u8 = !0").unwrap(), Value::Integer(255, Type::U8));
+    assert_eq!(eval(&mut nss, "let y: u16 = !0 - (1 << 15)").unwrap(), Value::Integer(32767, Type::U16));
+    assert!(matches!(eval(&mut nss, "let z: u8 = 200 + 100"), Err(EvalError::Overflow(_))));
+}
@@ -0,0 +1,69 @@
+use crate::{
+    error::EvalError,
+    value::Value,
+    r#type::Type,
+    test_utils::{eval, new_stack},
+};
+
+#[test]
+fn test_arrays() {
+    let mut nss = new_stack();
+    assert!(eval(&mut nss, "let mut a = [1u8, 2, 3]").is_ok());
+    assert_eq!(eval(&mut nss, "a[1]").unwrap(), Value::Integer(2, Type::U8));
+    assert!(eval(&mut nss, "a[1] = 20").is_ok());
+    assert_eq!(eval(&mut nss, "a[1] + a[2]").unwrap(), Value::Integer(23, Type::U8));
+    assert!(matches!(eval(&mut nss, "a[3]"), Err(EvalError::IndexOutOfBounds{index: 3, len: 3, ..})));
+    assert!(matches!(eval(&mut nss, "a[3] = 0"), Err(EvalError::IndexOutOfBounds{..})));
+    assert!(eval(&mut nss, "let b = [[0; 2]; 3]").is_ok());
+    assert_eq!(eval(&mut nss, "b.len() + b[2].len()").unwrap(), Value::Integer(5, Type::Usize));
+    assert!(matches!(eval(&mut nss, "b[0][0] = 1"), Err(EvalError::NotMutable(_))));
+    assert!(matches!(eval(&mut nss, "[1, true]"), Err(EvalError::TypeMismatch{..})));
+    assert!(matches!(eval(&mut nss, "[1, 2] == [true]"), Err(EvalError::TypeMismatch{..})));
+    assert!(matches!(eval(&mut nss, "[[1], [2]] == [[1u8], [3]]"), Ok(Value::Boolean(false))));
+    // la longueur fait partie du type d'un tableau
+    assert!(matches!(eval(&mut nss, "[[1u8], []]"), Err(EvalError::TypeMismatch{..})));
+    assert!(matches!(eval(&mut nss, "[1, 2] == [1]"), Err(EvalError::TypeMismatch{..})));
+    assert_eq!(Type::from(&eval(&mut nss, "[[0; 2]; 3]").unwrap()).to_string(), "[[isize; 2]; 3]");
+    // l'indexation suit les références
+    assert!(eval(&mut nss, "let r = &mut a").is_ok());
+    assert!(eval(&mut nss, "r[0] = 7").is_ok());
+    assert!(eval(&mut nss, "(*r)[2] = 9").is_ok());
+    assert_eq!(eval(&mut nss, "a == [7, 20, 9]").unwrap(), Value::Boolean(true));
+}
+
+#[test]
+fn test_vec() {
+    let mut nss = new_stack();
+    assert!(eval(&mut nss, "let mut v = vec![1, 2]").is_ok());
+    assert!(eval(&mut nss, "v.push(3)").is_ok());
+    assert_eq!(eval(&mut nss, "v.len()").unwrap(), Value::Integer(3, Type::Usize));
+    assert_eq!(eval(&mut nss, "v.pop()").unwrap(), Value::Integer(3, Type::Int));
+    assert!(matches!(eval(&mut nss, "v.push(true)"), Err(EvalError::TypeMismatch{..})));
+    assert_eq!(eval(&mut nss, "v == [1, 2]").unwrap(), Value::Boolean(true));
+    assert!(eval(&mut nss, "let w = v").is_ok());
+    assert!(matches!(eval(&mut nss, "v"), Err(EvalError::MovedValue(_))));
+    assert!(matches!(eval(&mut nss, "w.push(3)"), Err(EvalError::NotMutable(_))));
+    assert!(eval(&mut nss, "let mut e = Vec::new()").is_ok());
+    assert!(matches!(eval(&mut nss, "e.pop()"), Err(EvalError::EmptyVec(_))));
+    // un Vec vidé garde le type de ses éléments
+    assert!(eval(&mut nss, "e.push(1u8)").is_ok());
+    assert!(eval(&mut nss, "e.pop()").is_ok());
+    assert!(matches!(eval(&mut nss, "e.push(true)"), Err(EvalError::TypeMismatch{..})));
+    assert_eq!(eval(&mut nss, "e == [2]").unwrap(), Value::Boolean(false));
+}
+
+#[test]
+fn test_owned_elements() {
+    let mut nss = new_stack();
+    assert!(eval(&mut nss, r#"let s = vec![String::from("a")]"#).is_ok());
+    assert!(matches!(eval(&mut nss, "let t = s[0]"), Err(EvalError::CannotMoveOutOfIndex(_))));
+    assert_eq!(eval(&mut nss, "s[0].len()").unwrap(), Value::Integer(1, Type::Usize));
+    assert!(matches!(eval(&mut nss, r#"[String::from("b"); 2]"#), Err(EvalError::NotCopy(_))));
+    // le tampon du Vec et la String qu'il contient sont libérés avec lui
+    let buffer = match eval(&mut nss, "s").unwrap() {
+        Value::Vec(buffer, _) => buffer,
+        v => panic!("{} is not a Vec", v),
+    };
+    nss.pop();
+    assert!(matches!(nss.read_at(&buffer), Err(EvalError::UseAfterFree(_))));
+}
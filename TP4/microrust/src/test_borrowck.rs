@@ -0,0 +1,84 @@
+use crate::{
+    borrowck::BorrowEnv,
+    error::EvalError,
+    instruction::Instruction,
+    parser::Parse,
+};
+
+fn borrowck(env: &mut BorrowEnv, input: &str) -> Result<(), EvalError> {
+    Instruction::parse(input).unwrap().borrowck(env).map(|_| ())
+}
+
+#[test]
+fn test_write_to_borrowed() {
+    let mut env = BorrowEnv::new();
+    assert!(borrowck(&mut env, "let mut x = 1").is_ok());
+    assert!(borrowck(&mut env, "{let r = &x; x = 2}").is_err());
+    assert!(borrowck(&mut env, "x = 2").is_ok());
+    assert!(borrowck(&mut env, "let r = &x").is_ok());
+    match borrowck(&mut env, "x = 3") {
+        Err(EvalError::BorrowedValue(_)) => (),
+        _ => panic!("x should be borrowed"),
+    }
+}
+
+#[test]
+fn test_mutable_borrows() {
+    let mut env = BorrowEnv::new();
+    assert!(borrowck(&mut env, "let mut x = 1").is_ok());
+    assert!(borrowck(&mut env, "let a = &x").is_ok());
+    assert!(borrowck(&mut env, "let b = &x").is_ok());
+    assert!(borrowck(&mut env, "let mut y = 1").is_ok());
+    assert!(borrowck(&mut env, "let m = &mut y").is_ok());
+    match borrowck(&mut env, "let n = &mut y") {
+        Err(EvalError::CannotBorrowMut(_)) => (),
+        _ => panic!("y should not be borrowed mutably twice"),
+    }
+    match borrowck(&mut env, "let s = &y") {
+        Err(EvalError::CannotBorrowShared(_)) => (),
+        _ => panic!("y is borrowed as mutable"),
+    }
+}
+
+#[test]
+fn test_outlives_referent() {
+    let mut env = BorrowEnv::new();
+    assert!(borrowck(&mut env, "let mut r = Box::new(0)").is_ok());
+    match borrowck(&mut env, "{let z = 1; r = &z}") {
+        Err(EvalError::DoesNotLiveLongEnough(_)) => (),
+        _ => panic!("z should not outlive its block"),
+    }
+    match borrowck(&mut env, "{let z = 1; &z}") {
+        Err(EvalError::DoesNotLiveLongEnough(_)) => (),
+        _ => panic!("z should not outlive its block"),
+    }
+    assert!(borrowck(&mut env, "{let z = 1; let s = &z; *s}").is_ok());
+}
+
+#[test]
+fn test_move_borrowed() {
+    let mut env = BorrowEnv::new();
+    assert!(borrowck(&mut env, "let b = Box::new(1)").is_ok());
+    assert!(borrowck(&mut env, "let r = &b").is_ok());
+    match borrowck(&mut env, "let c = b") {
+        Err(EvalError::BorrowedValue(_)) => (),
+        _ => panic!("b is borrowed and cannot be moved"),
+    }
+    assert!(borrowck(&mut env, "let x = 1").is_ok());
+    assert!(borrowck(&mut env, "let s = &x").is_ok());
+    assert!(borrowck(&mut env, "let y = x").is_ok());
+}
+
+#[test]
+fn test_concat_moves_borrowed() {
+    let mut env = BorrowEnv::new();
+    assert!(borrowck(&mut env, r#"let s = String::from("ab")"#).is_ok());
+    assert!(borrowck(&mut env, "let r = &s").is_ok());
+    match borrowck(&mut env, r#"let t = s + "c""#) {
+        Err(EvalError::BorrowedValue(_)) => (),
+        _ => panic!("s is borrowed and cannot be moved by +"),
+    }
+    assert!(borrowck(&mut env, "let n = 1").is_ok());
+    assert!(borrowck(&mut env, "let m = &n").is_ok());
+    assert!(borrowck(&mut env, "let k = n + 1").is_ok());
+}
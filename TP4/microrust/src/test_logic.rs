@@ -0,0 +1,27 @@
+use crate::{
+    error::EvalError,
+    value::Value,
+    test_utils::{eval, new_stack},
+};
+
+#[test]
+fn test_short_circuit() {
+    let mut nss = new_stack();
+    assert_eq!(eval(&mut nss, "false && 1 / 0 == 0").unwrap(), Value::Boolean(false));
+    assert_eq!(eval(&mut nss, "true || 1 / 0 == 0").unwrap(), Value::Boolean(true));
+    assert!(matches!(eval(&mut nss, "true && 1 / 0 == 0"), Err(EvalError::DivisionByZero(_))));
+    assert!(matches!(eval(&mut nss, "false || 1"), Err(EvalError::TypeMismatch{..})));
+}
+
+#[test]
+fn test_polymorphic_equality() {
+    let mut nss = new_stack();
+    assert_eq!(eval(&mut nss, "true == true").unwrap(), Value::Boolean(true));
+    assert_eq!(eval(&mut nss, "() != ()").unwrap(), Value::Boolean(false));
+    assert!(eval(&mut nss, "let x = 1").is_ok());
+    assert!(eval(&mut nss, "let p = &x").is_ok());
+    assert_eq!(eval(&mut nss, "p == &x").unwrap(), Value::Boolean(true));
+    assert!(eval(&mut nss, "let b = Box::new(1)").is_ok());
+    assert_eq!(eval(&mut nss, "b != p").unwrap(), Value::Boolean(true));
+    assert!(matches!(eval(&mut nss, "1 == true"), Err(EvalError::TypeMismatch{..})));
+}
@@ -2,39 +2,44 @@ use crate::{
     namespace::NameSpace,
     identifier::Identifier,
     error::EvalError,
+    memorycell::MemoryCell,
+    value::Value,
+    r#type::Type,
 };
 
-#[cfg(test)]
-mod test_namespace {
-
-    use super::*;
-
-    #[test]
-    fn test_declare() {
-        let mut ns = NameSpace::new();
-        let id = Identifier::from("x");
-        assert!(ns.declare(&id, 42).is_ok());
-        match ns.declare(&id, 42) {
-            Ok(_) => panic!("Identifier should not be declared twice"),
-            Err(EvalError::AlreadyDefined(id2)) => assert_eq!(id, id2),
-            Err(_) => panic!("Unexpected error"),
-        }
+#[test]
+fn test_declare() {
+    let mut ns = NameSpace::new();
+    let id = Identifier::from("x");
+    assert!(ns.declare(&id, MemoryCell::new(false, Value::Integer(42, Type::Int))).is_ok());
+    match ns.declare(&id, MemoryCell::new(false, Value::Integer(42, Type::Int))) {
+        Ok(_) => panic!("Identifier should not be declared twice"),
+        Err(EvalError::AlreadyDefined(id2)) => assert_eq!(id, id2),
+        Err(_) => panic!("Unexpected error"),
     }
+}
 
-    #[test]
-    fn test_find() {
-        let mut ns = NameSpace::new();
-        let id = Identifier::from("x");
-        match ns.find(&id) {
-            Ok(_) => panic!("Identifier should not be found"),
-            Err(EvalError::Undefined(id2)) => assert_eq!(id, id2),
-            Err(_) => panic!("Unexpected error"),
-        }
-        assert!(ns.declare(&id, 42).is_ok());
-        match ns.find(&id) {
-            Ok(42) => (),
-            _ => panic!("Identifier should be found"),
-        }
+#[test]
+fn test_find() {
+    let mut ns = NameSpace::new();
+    let id = Identifier::from("x");
+    match ns.find(&id) {
+        Ok(_) => panic!("Identifier should not be found"),
+        Err(EvalError::Undefined(id2)) => assert_eq!(id, id2),
+        Err(_) => panic!("Unexpected error"),
     }
+    assert!(ns.declare(&id, MemoryCell::new(false, Value::Integer(42, Type::Int))).is_ok());
+    match ns.find(&id) {
+        Ok(Value::Integer(42, Type::Int)) => (),
+        _ => panic!("Identifier should be found"),
+    }
+}
 
+#[test]
+fn test_keyword_display() {
+    for keyword in ["let", "while", "Box", "RawPtr", "Rc", "clone", "as"] {
+        assert_eq!(Identifier::from(keyword).to_string(), format!("r#{}", keyword));
+    }
+    assert_eq!(Identifier::from("r#letter").to_string(), "letter");
+    assert_eq!(Identifier::from("cloned").to_string(), "cloned");
 }
@@ -0,0 +1,96 @@
+use crate::{
+    namespace::NameSpace,
+    namespacestack::NameSpaceStack,
+    identifier::Identifier,
+    error::EvalError,
+    memory::Address,
+    parsing::ptr_kind::PtrKind,
+    value::Value,
+    r#type::Type,
+};
+
+#[test]
+fn test_find() {
+    let mut nss = NameSpaceStack::new();
+    nss.push(NameSpace::new());
+    let x = Identifier::from("x");
+    let y = Identifier::from("y");
+    let z = Identifier::from("z");
+    assert!(nss.declare(&x, false, &Value::Integer(0, Type::Int)).is_ok());
+    assert!(nss.declare(&y, false, &Value::Integer(0, Type::Int)).is_ok());
+    nss.push(NameSpace::new());
+    assert!(nss.declare(&x, false, &Value::Integer(1, Type::Int)).is_ok());
+    match nss.find(&x) {
+        Ok(Value::Integer(1, Type::Int)) => (),
+        _ => panic!("Expected 1"),
+    }
+    match nss.find(&y) {
+        Ok(Value::Integer(0, Type::Int)) => (),
+        _ => panic!("Expected 0"),
+    }
+    match nss.find(&z) {
+        Err(EvalError::Undefined(id)) => assert_eq!(id, z),
+        _ => panic!("Expected undefined"),
+    }
+}
+
+#[test]
+fn test_box_dropped_on_pop() {
+    let mut nss = NameSpaceStack::new();
+    nss.push(NameSpace::new());
+    nss.push(NameSpace::new());
+    let inner = nss.malloc(PtrKind::Box, Value::Integer(1, Type::Int));
+    let outer = nss.malloc(PtrKind::Box, Value::Pointer(inner.clone()));
+    assert!(nss.declare(&Identifier::from("b"), false, &Value::Pointer(outer.clone())).is_ok());
+    nss.pop();
+    for address in [outer, inner] {
+        match nss.read_at(&address) {
+            Err(EvalError::UseAfterFree(None)) => (),
+            _ => panic!("Box should be freed with its owner"),
+        }
+    }
+    match nss.read_at(&Address::HeapAddress(2)) {
+        Err(EvalError::NonAllocatedCell(None)) => (),
+        _ => panic!("Cell should not be allocated"),
+    }
+}
+
+#[test]
+fn test_rc_freed_with_last_reference() {
+    let mut nss = NameSpaceStack::new();
+    nss.push(NameSpace::new());
+    let rc = Value::Pointer(nss.malloc(PtrKind::Rc, Value::Integer(1, Type::Int)));
+    assert!(nss.rc_clone(&rc.to_address().unwrap()).is_ok());
+    assert_eq!(nss.strong_count(&rc), Some(2));
+    nss.drop_value(&rc);
+    assert_eq!(nss.strong_count(&rc), Some(1));
+    nss.drop_value(&rc);
+    assert_eq!(nss.strong_count(&rc), None);
+}
+
+#[test]
+fn test_raw_pointer_diagnostics() {
+    let mut nss = NameSpaceStack::new();
+    nss.push(NameSpace::new());
+    let raw = nss.malloc_uninitialized(PtrKind::RawPtr);
+    match nss.read_at(&raw) {
+        Err(EvalError::NonInitializedValue(None)) => (),
+        _ => panic!("Expected uninitialized read"),
+    }
+    assert!(nss.free(&raw).is_ok());
+    match nss.free(&raw) {
+        Err(EvalError::DoubleFree(None)) => (),
+        _ => panic!("Expected double free"),
+    }
+    nss.push(NameSpace::new());
+    let x = Identifier::from("x");
+    assert!(nss.declare(&x, false, &Value::Integer(0, Type::Int)).is_ok());
+    let stack = nss.get_address(&x).unwrap();
+    nss.pop();
+    nss.push(NameSpace::new());
+    assert!(nss.declare(&x, false, &Value::Integer(1, Type::Int)).is_ok());
+    match nss.read_at(&stack) {
+        Err(EvalError::DanglingPointer(None)) => (),
+        _ => panic!("Expected dangling pointer"),
+    }
+}
@@ -0,0 +1,50 @@
+use crate::{
+    error::{Error, EvalError},
+    instruction::Instruction,
+    namespacestack::NameSpaceStack,
+    parser::{Parse, ParseError},
+    test_utils::new_stack,
+};
+
+fn run(nss: &mut NameSpaceStack, input: &str) -> Result<String, Error> {
+    Instruction::parse(input)?.exec(nss)?;
+    Ok(nss.take_output())
+}
+
+#[test]
+fn test_placeholders() {
+    let mut nss = new_stack();
+    nss.capture_output();
+    assert!(run(&mut nss, "let x = 42").is_ok());
+    assert_eq!(run(&mut nss, r#"println!("{} + {0} = {sum}", x, sum = x + x)"#).unwrap(), "42 + 42 = 84\n");
+    assert_eq!(run(&mut nss, r#"print!("{x}{{}}")"#).unwrap(), "42{}");
+    assert!(nss.close_line());
+    assert_eq!(run(&mut nss, r#"println!("{:?} {:?} {}", "a\"b", 'c', "a\"b")"#).unwrap(), "\"a\\\"b\" 'c' a\"b\n");
+}
+
+#[test]
+fn test_width_and_precision() {
+    let mut nss = new_stack();
+    nss.capture_output();
+    assert_eq!(run(&mut nss, r#"println!("[{:5}][{:5}][{:<4}][{:-^7}]", 42, "ab", 1u8, true)"#).unwrap(), "[   42][ab   ][1   ][-true--]\n");
+    assert_eq!(run(&mut nss, r#"println!("[{:>6.3}]", "abcdef")"#).unwrap(), "[   abc]\n");
+    assert_eq!(run(&mut nss, r#"println!("[{:08}][{:<05}][{:03}]", 5, 0 - 42, "a")"#).unwrap(), "[00000005][-0042][a  ]\n");
+}
+
+#[test]
+fn test_checked_at_parse_time() {
+    for input in [
+        r#"println!("{} {}", 1)"#,
+        r#"println!("{}", 1, 2)"#,
+        r#"println!("{a}", a = 1, b = 2)"#,
+        r#"println!("{a}", a = 1, 2)"#,
+        r#"println!("{:x}", 1)"#,
+        r#"println!("{")"#,
+        r#"println!("}")"#,
+    ] {
+        assert!(matches!(Instruction::parse(input), Err(ParseError::InvalidFormat(_))), "{}", input);
+    }
+    let mut nss = new_stack();
+    nss.capture_output();
+    assert!(matches!(run(&mut nss, r#"println!("{y}")"#), Err(Error::EvalError(EvalError::Undefined(_)))));
+}
@@ -0,0 +1,48 @@
+use crate::{
+    error::EvalError,
+    instruction::Instruction,
+    parser::Parse,
+    value::Value,
+    r#type::Type,
+    test_utils::{eval, new_stack},
+};
+
+#[test]
+fn test_literals() {
+    let mut nss = new_stack();
+    assert_eq!(eval(&mut nss, r#""a\"b\\c\n\u{e9}""#).unwrap().to_str().unwrap().as_ref(), "a\"b\\c\né");
+    assert_eq!(eval(&mut nss, r"'\''").unwrap(), Value::Char('\''));
+    assert_eq!(eval(&mut nss, r"'\t' as u8").unwrap(), Value::Integer(9, Type::U8));
+    assert!(Instruction::parse("'ab'").is_err());
+    assert!(Instruction::parse(r#""\q""#).is_err());
+    // l'affichage d'une instruction doit pouvoir être relu
+    assert!(eval(&mut nss, r#"let s: &str = "\"\n""#).is_ok());
+}
+
+#[test]
+fn test_string_moves() {
+    let mut nss = new_stack();
+    assert!(eval(&mut nss, r#"let s1 = String::from("hello")"#).is_ok());
+    assert!(eval(&mut nss, "let s2 = s1").is_ok());
+    assert!(matches!(eval(&mut nss, "s1"), Err(EvalError::MovedValue(_))));
+    assert!(eval(&mut nss, r#"let s3 = s2 + " world""#).is_ok());
+    assert!(matches!(eval(&mut nss, "s2"), Err(EvalError::MovedValue(_))));
+    assert_eq!(eval(&mut nss, "s3.len()").unwrap(), Value::Integer(11, Type::Usize));
+    // un &str est copié
+    assert!(eval(&mut nss, r#"let t = "x""#).is_ok());
+    assert!(eval(&mut nss, "let u = t").is_ok());
+    assert!(eval(&mut nss, "t").is_ok());
+    assert!(matches!(eval(&mut nss, "t + u"), Err(EvalError::TypeMismatch{..})));
+}
+
+#[test]
+fn test_string_comparisons() {
+    let mut nss = new_stack();
+    assert!(eval(&mut nss, r#"let s = String::from("abc")"#).is_ok());
+    assert_eq!(eval(&mut nss, r#"s == "abc""#).unwrap(), Value::Boolean(true));
+    assert_eq!(eval(&mut nss, r#"s < "abd""#).unwrap(), Value::Boolean(true));
+    assert_eq!(eval(&mut nss, "'b' >= 'a'").unwrap(), Value::Boolean(true));
+    assert_eq!(eval(&mut nss, "1 < 1").unwrap(), Value::Boolean(false));
+    assert_eq!(eval(&mut nss, "(&s).len() == 3").unwrap(), Value::Boolean(true));
+    assert!(matches!(eval(&mut nss, "s == 'a'"), Err(EvalError::TypeMismatch{..})));
+}
@@ -0,0 +1,68 @@
+use crate::{
+    error::EvalError,
+    instruction::Instruction,
+    parser::{Parse, ParseError},
+    value::Value,
+    r#type::Type,
+    test_utils::{eval, new_stack},
+};
+
+#[test]
+fn test_tuples() {
+    let mut nss = new_stack();
+    assert_eq!(eval(&mut nss, "()").unwrap(), Value::UNIT);
+    assert!(eval(&mut nss, "let mut t = (1u8, (true, 'c'))").is_ok());
+    assert_eq!(eval(&mut nss, "t.1.0").unwrap(), Value::Boolean(true));
+    assert!(eval(&mut nss, "t.0 = 7").is_ok());
+    assert_eq!(eval(&mut nss, "t.0").unwrap(), Value::Integer(7, Type::U8));
+    assert!(matches!(eval(&mut nss, "t.2"), Err(EvalError::NoField{..})));
+    assert_eq!(eval(&mut nss, "(1, 2).1").unwrap(), Value::Integer(2, Type::Int));
+    assert_eq!(Type::from(&eval(&mut nss, "(5,)").unwrap()).to_string(), "(isize,)");
+    assert_eq!(eval(&mut nss, "t == (7, (true, 'c'))").unwrap(), Value::Boolean(true));
+    // l'annotation fixe le type des entiers sans suffixe
+    assert!(eval(&mut nss, "let u: (i8, ()) = (-1, ())").is_ok());
+    assert_eq!(eval(&mut nss, "u.0").unwrap(), Value::Integer(-1, Type::I8));
+    assert!(matches!(eval(&mut nss, "let v: (u8, bool) = (1, 2)"), Err(EvalError::TypeMismatch{..})));
+}
+
+#[test]
+fn test_destructuring() {
+    let mut nss = new_stack();
+    assert!(eval(&mut nss, "let (x, mut y) = (1, (2, 3))").is_ok());
+    assert!(matches!(eval(&mut nss, "x = 5"), Err(EvalError::NotMutable(_))));
+    assert!(eval(&mut nss, "y = (4, 5)").is_ok());
+    assert!(eval(&mut nss, "let ((a, b), c) = ((true, 'a'), ())").is_ok());
+    assert_eq!(eval(&mut nss, "b").unwrap(), Value::Char('a'));
+    assert_eq!(eval(&mut nss, "c").unwrap(), Value::UNIT);
+    assert!(matches!(eval(&mut nss, "let (p, q) = (1, 2, 3)"), Err(EvalError::PatternMismatch{..})));
+    assert!(matches!(Instruction::parse("let (z, z) = (1, 2)"), Err(ParseError::BoundTwice(_))));
+    // une String dans un tuple est déplacée avec lui
+    assert!(eval(&mut nss, r#"let s = (String::from("s"), 1)"#).is_ok());
+    assert!(eval(&mut nss, "let (s1, n) = s").is_ok());
+    assert!(matches!(eval(&mut nss, "s"), Err(EvalError::MovedValue(_))));
+    assert_eq!(eval(&mut nss, "s1.len() + n as usize").unwrap(), Value::Integer(2, Type::Usize));
+}
+
+#[test]
+fn test_partial_move() {
+    let mut nss = new_stack();
+    assert!(eval(&mut nss, r#"let mut t = (String::from("a"), 1)"#).is_ok());
+    assert!(eval(&mut nss, "let u = t.0").is_ok());
+    assert_eq!(eval(&mut nss, "t.1").unwrap(), Value::Integer(1, Type::Int));
+    assert!(matches!(eval(&mut nss, "t"), Err(EvalError::MovedValue(_))));
+    assert!(matches!(eval(&mut nss, "t.0"), Err(EvalError::MovedValue(_))));
+    // réécrire le champ déplacé rend le tuple à nouveau utilisable
+    assert!(eval(&mut nss, r#"t.0 = String::from("bc")"#).is_ok());
+    assert_eq!(eval(&mut nss, "t.0.len() + u.len()").unwrap(), Value::Integer(3, Type::Usize));
+    assert!(eval(&mut nss, r#"let a = [(String::from("x"), 2)]"#).is_ok());
+    assert!(matches!(eval(&mut nss, "let x = a[0].0"), Err(EvalError::CannotMoveOutOfIndex(_))));
+    // les champs restants sont libérés avec le tuple
+    assert!(eval(&mut nss, r#"let p = (String::from("p"), String::from("q"))"#).is_ok());
+    let buffer = match eval(&mut nss, "p.1").unwrap() {
+        Value::String(buffer) => buffer,
+        v => panic!("{} is not a String", v),
+    };
+    assert!(eval(&mut nss, "let p0 = p.0").is_ok());
+    nss.pop();
+    assert!(matches!(nss.read_at(&buffer), Err(EvalError::UseAfterFree(_))));
+}
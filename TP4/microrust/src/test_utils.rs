@@ -0,0 +1,19 @@
+use crate::{
+    error::EvalError,
+    instruction::Instruction,
+    namespace::NameSpace,
+    namespacestack::NameSpaceStack,
+    parser::Parse,
+    value::Value,
+};
+
+// valeur d'une instruction exécutée dans `nss`
+pub fn eval(nss: &mut NameSpaceStack, input: &str) -> Result<Value, EvalError> {
+    Instruction::parse(input).unwrap().exec(nss).map(|(_, value)| value)
+}
+
+pub fn new_stack() -> NameSpaceStack {
+    let mut nss = NameSpaceStack::new();
+    nss.push(NameSpace::new());
+    nss
+}
@@ -0,0 +1,137 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Int,
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    Usize,
+    // littéral entier sans suffixe, dont le type n'est pas encore fixé
+    IntLiteral,
+    Bool,
+    Char,
+    // chaîne littérale, copiée
+    Str,
+    // chaîne possédée, allouée dans le tas
+    String,
+    // tableau de taille fixe, gardé en place, et sa longueur ; le type des
+    // éléments d'un tableau vide n'est pas connu
+    Array(Option<Box<Type>>, usize),
+    // tableau extensible, dont les éléments sont dans le tas
+    Vec(Option<Box<Type>>),
+    // `()` est le tuple vide
+    Tuple(Vec<Type>),
+    Pointer,
+}
+
+use std::fmt::{self, Display};
+
+// `()`, `(a,)`, `(a, b)`... : un tuple à un élément garde sa virgule
+pub fn tuple<T: Display>(elements: &[T]) -> String {
+    match elements {
+        [element] => format!("({},)", element),
+        _ => format!("({})", elements.iter().map(|e| e.to_string()).collect::<Vec<_>>().join(", ")),
+    }
+}
+
+// type des éléments d'un tableau, `_` s'il n'est pas connu
+fn element(element: &Option<Box<Type>>) -> String {
+    element.as_ref().map(|ty| ty.to_string()).unwrap_or("_".to_string())
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Type::*;
+        match self {
+            Int => write!(f, "isize"),
+            I8 => write!(f, "i8"),
+            I16 => write!(f, "i16"),
+            I32 => write!(f, "i32"),
+            I64 => write!(f, "i64"),
+            U8 => write!(f, "u8"),
+            U16 => write!(f, "u16"),
+            U32 => write!(f, "u32"),
+            U64 => write!(f, "u64"),
+            Usize => write!(f, "usize"),
+            IntLiteral => write!(f, "{{integer}}"),
+            Bool => write!(f, "bool"),
+            Char => write!(f, "char"),
+            Str => write!(f, "&str"),
+            String => write!(f, "String"),
+            Array(element, len) => write!(f, "[{}; {}]", self::element(element), len),
+            Vec(element) => write!(f, "Vec<{}>", self::element(element)),
+            Tuple(types) => write!(f, "{}", tuple(types)),
+            Pointer => write!(f, "ptr"),
+        }
+    }
+}
+
+impl Type {
+    pub const UNIT: Type = Type::Tuple(Vec::new());
+
+    // bornes des valeurs d'un type entier ; un littéral sans suffixe
+    // calcule dans celles de `isize`, son type par défaut
+    pub fn int_range(&self) -> Option<(i128, i128)> {
+        use Type::*;
+        match self {
+            I8 => Some((i8::MIN as i128, i8::MAX as i128)),
+            I16 => Some((i16::MIN as i128, i16::MAX as i128)),
+            I32 => Some((i32::MIN as i128, i32::MAX as i128)),
+            I64 => Some((i64::MIN as i128, i64::MAX as i128)),
+            Int | IntLiteral => Some((isize::MIN as i128, isize::MAX as i128)),
+            U8 => Some((0, u8::MAX as i128)),
+            U16 => Some((0, u16::MAX as i128)),
+            U32 => Some((0, u32::MAX as i128)),
+            U64 => Some((0, u64::MAX as i128)),
+            Usize => Some((0, usize::MAX as i128)),
+            Bool | Char | Str | String | Array(..) | Vec(_) | Tuple(_) | Pointer => None,
+        }
+    }
+
+    pub fn is_int(&self) -> bool {
+        self.int_range().is_some()
+    }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(self.int_range(), Some((min, _)) if min < 0)
+    }
+
+    pub fn contains(&self, i: i128) -> bool {
+        matches!(self.int_range(), Some((min, max)) if min <= i && i <= max)
+    }
+
+    pub fn bits(&self) -> u32 {
+        let (min, max) = self.int_range().expect("not an integer type");
+        (max - min + 1).trailing_zeros()
+    }
+
+    // troncature en complément à deux, comme `as` et le mode `--wrapping`
+    pub fn wrap(&self, i: i128) -> i128 {
+        let (min, max) = self.int_range().expect("not an integer type");
+        (i - min).rem_euclid(max - min + 1) + min
+    }
+
+    // type commun de deux opérandes : un littéral sans suffixe prend le
+    // type entier de l'autre, élément par élément dans un tuple ou un
+    // tableau de même longueur ; un tableau vide prend le type d'éléments
+    // de l'autre
+    pub fn unify(&self, other: &Type) -> Option<Type> {
+        let elements = |e1: &Option<Box<Type>>, e2: &Option<Box<Type>>| match (e1, e2) {
+            (None, e) | (e, None) => Some(e.clone()),
+            (Some(t1), Some(t2)) => t1.unify(t2).map(|t| Some(Box::new(t))),
+        };
+        match (self, other) {
+            (t1, t2) if t1 == t2 => Some(t1.clone()),
+            (Type::IntLiteral, t) | (t, Type::IntLiteral) if t.is_int() => Some(t.clone()),
+            (Type::Array(e1, n1), Type::Array(e2, n2)) if n1 == n2 => elements(e1, e2).map(|e| Type::Array(e, *n1)),
+            (Type::Vec(e1), Type::Vec(e2)) => elements(e1, e2).map(Type::Vec),
+            (Type::Tuple(types1), Type::Tuple(types2)) if types1.len() == types2.len() =>
+                types1.iter().zip(types2).map(|(t1, t2)| t1.unify(t2)).collect::<Option<_>>().map(Type::Tuple),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,32 @@
+////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unop {
+    Neg,
+    Not,
+}
+////////////////////////////////////////////////////////////////////////////////
+
+use std::fmt::{self, Display};
+
+impl Display for Unop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Unop::*;
+        match self {
+            Neg => write!(f, "-"),
+            Not => write!(f, "!"),
+        }
+    }
+}
+
+
+use crate::parser::ParseError;
+use crate::parsing::unop::Unop as ParseUnop;
+
+impl From<ParseUnop> for Result<Unop, ParseError> {
+    fn from(unop: ParseUnop) -> Self {
+        match unop {
+            ParseUnop::Neg => Ok(Unop::Neg),
+            ParseUnop::Not => Ok(Unop::Not),
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::memory::Address;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    // valeur et type entier ; la valeur est toujours dans les bornes du type
+    Integer(i128, Type),
+    Boolean(bool),
+    Char(char),
+    Str(Rc<str>),
+    // le tampon d'une String est une cellule du tas, possédée comme une Box,
+    // qui contient la chaîne
+    String(Address),
+    Array(Vec<Value>),
+    // comme pour une String, le tampon d'un Vec est une cellule du tas qui
+    // contient le tableau de ses éléments, dont le type est gardé avec le Vec
+    Vec(Address, Option<Box<Type>>),
+    // `()` est le tuple vide
+    Tuple(Vec<Value>),
+    Pointer(Address),
+}
+
+impl fmt::Display for Value {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Integer(i, _) => write!(f, "{}", i),
+            Value::Boolean(b) => write!(f, "{}", b),
+            Value::Char(c) => write!(f, "{}", c),
+            Value::Str(s) => write!(f, "{}", s),
+            Value::Array(_) | Value::Tuple(_) => write!(f, "{}", self.debug()),
+            // le contenu est dans le tas : voir `NameSpaceStack::contents`
+            Value::String(a) | Value::Vec(a, _) | Value::Pointer(a) => write!(f, "{}", a),
+        }
+    }
+}
+
+use crate::{parsing::parsedvalue::ParsedValue, r#type::{self, Type}};
+
+impl From<ParsedValue> for Value {
+    fn from(pv: ParsedValue) -> Self {
+        match pv {
+            ParsedValue::Integer(i, t) => Value::Integer(i, t),
+            ParsedValue::Boolean(b) => Value::Boolean(b),
+            ParsedValue::Char(c) => Value::Char(c),
+            ParsedValue::Str(s) => Value::Str(Rc::from(s)),
+            ParsedValue::Pointer(a) => Value::Pointer(a),
+        }
+    }
+}
+
+impl From<&Value> for Type {
+    fn from(v: &Value) -> Self {
+        match v {
+            Value::Integer(_, t) => t.clone(),
+            Value::Boolean(_) => Type::Bool,
+            Value::Char(_) => Type::Char,
+            Value::Str(_) => Type::Str,
+            Value::String(..) => Type::String,
+            Value::Array(values) => Type::Array(values.iter().map(Type::from)
+                .reduce(|t1, t2| t1.unify(&t2).unwrap_or(t1))
+                .map(Box::new), values.len()),
+            Value::Vec(_, element) => Type::Vec(element.clone()),
+            Value::Tuple(values) => Type::Tuple(values.iter().map(Type::from).collect()),
+            Value::Pointer(_) => Type::Pointer,
+        }
+    }
+}
+
+impl Value {
+    pub const UNIT: Value = Value::Tuple(Vec::new());
+
+    // écriture de `{:?}` : les chaînes et les caractères sont entre guillemets
+    pub fn debug(&self) -> String {
+        match self {
+            Value::Str(s) => format!("{:?}", s),
+            Value::Char(c) => format!("{:?}", c),
+            Value::Array(values) =>
+                format!("[{}]", values.iter().map(Value::debug).collect::<Vec<_>>().join(", ")),
+            Value::Tuple(values) => r#type::tuple(&values.iter().map(Value::debug).collect::<Vec<_>>()),
+            _ => self.to_string(),
+        }
+    }
+    pub fn to_int(&self) -> Result<(i128, Type), Type> {
+        match self {
+            Value::Integer(i, t) => Ok((*i, t.clone())),
+            _ => Err(Type::from(self)),
+        }
+    }
+    pub fn to_bool(&self) -> Result<bool, Type> {
+        match self {
+            Value::Boolean(b) => Ok(*b),
+            _ => Err(Type::from(self)),
+        }
+    }
+    // contenu d'une chaîne littérale, ou lu dans le tampon d'une String
+    pub fn to_str(&self) -> Result<Rc<str>, Type> {
+        match self {
+            Value::Str(s) => Ok(s.clone()),
+            _ => Err(Type::from(self)),
+        }
+    }
+    pub fn to_address(&self) -> Result<Address, Type> {
+        match self {
+            Value::Pointer(a) => Ok(a.clone()),
+            _ => Err(Type::from(self)),
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use std::rc::Rc;
+
+use crate::error::EvalError;
+use crate::identifier::Identifier;
+use crate::parsing::binop::Binop;
+use crate::parsing::expression::{Expression, ExpressionKind};
+use crate::parsing::function::Function;
+use crate::parsing::instruction::{Instruction, InstructionKind};
+use crate::parsing::span::Span;
+use crate::value::Value;
+use ExpressionKind::*;
+
+// instructions de la machine à pile ; un `usize` désigne une expression de
+// `Chunk::exprs`, citée dans les erreurs, sauf pour les sauts (indice d'op)
+// et les variables, désignées par leur NameSpace (compté depuis la base de
+// l'appel en cours) et leur indice dans celui-ci
+#[derive(Debug, Clone)]
+pub enum Op {
+    Const(Value),
+    Load{depth: usize, slot: usize, expr: usize},
+    // vérifient le type du sommet de pile, sans le dépiler
+    CheckInt(usize),
+    CheckBool(usize),
+    CheckAddress(usize),
+    // opérandes déjà vérifiés ; `expr` est l'opération et `rhs` le diviseur
+    Binary{op: Binop, expr: usize, rhs: usize},
+    Jump(usize),
+    JumpIfFalse(usize),
+    NewPtr(usize),
+    Read(usize),
+    AddressOf(Identifier),
+    Fail(EvalError),
+    // moves, depuis le propriétaire de la valeur comme dans `is_owner` :
+    // `Move` lit et déplace une variable, `ReadOwner` remplace (propriétaire
+    // de `e`, `e`) par (propriétaire de `*e`, `*e`) et `MoveAt` lit et déplace `*e`
+    Move{depth: usize, slot: usize, expr: usize},
+    ReadOwner(usize),
+    MoveAt(usize),
+    Store(usize),
+    Assign{depth: usize, slot: usize, expr: usize},
+    Free(usize),
+    Pop,
+    Declare(Identifier, bool),
+    EnterBlock,
+    ExitBlock,
+    PrepareCall(Identifier, usize),
+    Call(usize),
+    DefineFunction(Rc<Function>),
+    Return,
+}
+
+// code compilé : `spans[pc]` est la position du nœud qui a produit `ops[pc]`,
+// à laquelle sont rattachées ses erreurs
+// `scopes` suit, pendant la compilation, les variables de chaque NameSpace
+#[derive(Debug, Default)]
+pub struct Chunk {
+    pub ops: Vec<Op>,
+    pub spans: Vec<Span>,
+    pub exprs: Vec<Expression>,
+    scopes: Vec<Vec<Identifier>>,
+}
+
+impl Chunk {
+    fn emit(&mut self, op: Op, span: Span) -> usize {
+        self.ops.push(op);
+        self.spans.push(span);
+        self.ops.len() - 1
+    }
+
+    fn expr(&mut self, e: &Expression) -> usize {
+        self.exprs.push(e.clone());
+        self.exprs.len() - 1
+    }
+
+    // NameSpace et indice de la variable `id`, cherchée comme le fait
+    // `NameSpaceStack::find`
+    fn resolve(&self, id: &Identifier) -> Option<(usize, usize)> {
+        self.scopes.iter().enumerate().rev()
+            .find_map(|(depth, scope)| scope.iter().position(|x| x == id).map(|slot| (depth, slot)))
+    }
+
+    // fait sauter l'op `at` à la fin actuelle du code
+    fn patch(&mut self, at: usize) {
+        let target = self.ops.len();
+        match &mut self.ops[at] {
+            Op::Jump(to) | Op::JumpIfFalse(to) => *to = target,
+            op => unreachable!("patch expected a jump, found {:?}", op),
+        }
+    }
+}
+
+
+impl Expression {
+    // chaque expression compilée laisse sa valeur en sommet de pile
+    fn compile(&self, chunk: &mut Chunk) {
+        match &self.kind {
+            Const(v) => { chunk.emit(Op::Const(Value::from(*v)), self.span); },
+            Identifier(id) => match chunk.resolve(id) {
+                Some((depth, slot)) => {
+                    let expr = chunk.expr(self);
+                    chunk.emit(Op::Load{depth, slot, expr}, self.span);
+                },
+                None => {
+                    let err = EvalError::Undefined(id.clone()).with_expression_info(self.clone());
+                    chunk.emit(Op::Fail(err), self.span);
+                },
+            },
+            BinOp(e1, op, e2) => {
+                let check = |chunk: &mut Chunk, e: &Expression| {
+                    let i = chunk.expr(e);
+                    match op {
+                        Binop::Eq | Binop::Neq => (),
+                        Binop::And | Binop::Or => { chunk.emit(Op::CheckBool(i), e.span); },
+                        _ => { chunk.emit(Op::CheckInt(i), e.span); },
+                    }
+                    i
+                };
+                e1.compile(chunk);
+                check(chunk, e1);
+                e2.compile(chunk);
+                let rhs = check(chunk, e2);
+                let expr = chunk.expr(self);
+                chunk.emit(Op::Binary{op: *op, expr, rhs}, self.span);
+            },
+            Conditional{cond, cond_true, cond_false} => {
+                cond.compile_condition(chunk);
+                let jump_false = chunk.emit(Op::JumpIfFalse(0), self.span);
+                cond_true.compile(chunk);
+                let jump_end = chunk.emit(Op::Jump(0), self.span);
+                chunk.patch(jump_false);
+                cond_false.compile(chunk);
+                chunk.patch(jump_end);
+            },
+            NewPtr => {
+                let e = chunk.expr(self);
+                chunk.emit(Op::NewPtr(e), self.span);
+            },
+            Deref(inner) => {
+                inner.compile(chunk);
+                let i = chunk.expr(inner);
+                chunk.emit(Op::CheckAddress(i), inner.span);
+                let e = chunk.expr(self);
+                chunk.emit(Op::Read(e), self.span);
+            },
+            AmpersAnd(inner) => inner.compile_place(chunk),
+            // la fonction est cherchée, et son arité vérifiée, avant d'évaluer les arguments
+            Call(id, args) => {
+                chunk.emit(Op::PrepareCall(id.clone(), args.len()), self.span);
+                for arg in args {
+                    arg.compile_moved(chunk);
+                }
+                chunk.emit(Op::Call(args.len()), self.span);
+            },
+        }
+    }
+
+    fn compile_condition(&self, chunk: &mut Chunk) {
+        self.compile(chunk);
+        let i = chunk.expr(self);
+        chunk.emit(Op::CheckBool(i), self.span);
+    }
+
+    // adresse désignée par une expression gauche, comme `eval_to_address`
+    fn compile_place(&self, chunk: &mut Chunk) {
+        match &self.kind {
+            Identifier(id) => { chunk.emit(Op::AddressOf(id.clone()), self.span); },
+            Deref(inner) => {
+                inner.compile(chunk);
+                let i = chunk.expr(inner);
+                chunk.emit(Op::CheckAddress(i), inner.span);
+            },
+            _ => { chunk.emit(Op::Fail(EvalError::NonAllocatedCell(Some(self.clone()))), self.span); },
+        }
+    }
+
+    // seules les variables et les déréférencements peuvent être déplacés
+    fn compile_moved(&self, chunk: &mut Chunk) {
+        match &self.kind {
+            Identifier(id) => match chunk.resolve(id) {
+                Some((depth, slot)) => {
+                    let expr = chunk.expr(self);
+                    chunk.emit(Op::Move{depth, slot, expr}, self.span);
+                },
+                None => self.compile(chunk),
+            },
+            Deref(inner) => {
+                inner.compile_owner(chunk);
+                let i = chunk.expr(inner);
+                chunk.emit(Op::CheckAddress(i), inner.span);
+                let e = chunk.expr(self);
+                chunk.emit(Op::MoveAt(e), self.span);
+            },
+            _ => self.compile(chunk),
+        }
+    }
+
+    // empile un booléen, vrai si `self` est propriétaire de sa valeur, puis
+    // cette valeur
+    fn compile_owner(&self, chunk: &mut Chunk) {
+        match &self.kind {
+            Deref(inner) => {
+                inner.compile_owner(chunk);
+                let i = chunk.expr(inner);
+                chunk.emit(Op::CheckAddress(i), inner.span);
+                let e = chunk.expr(self);
+                chunk.emit(Op::ReadOwner(e), self.span);
+            },
+            _ => {
+                let owner = matches!(self.kind, Identifier(_));
+                chunk.emit(Op::Const(Value::Boolean(owner)), self.span);
+                self.compile(chunk);
+            },
+        }
+    }
+}
+
+
+impl Instruction {
+    // chaque instruction compilée laisse sa valeur en sommet de pile
+    fn compile_into(&self, chunk: &mut Chunk) {
+        match &self.kind {
+            InstructionKind::Expr(e) => e.compile(chunk),
+            // une déclaration qui échoue interrompt le bloc : la variable
+            // n'a d'indice que si elle est nouvelle
+            InstructionKind::Let{id, mutable, expr} => {
+                expr.compile_moved(chunk);
+                chunk.emit(Op::Declare(id.clone(), *mutable), self.span);
+                let scope = chunk.scopes.last_mut().unwrap();
+                if !scope.contains(id) {
+                    scope.push(id.clone());
+                }
+            },
+            InstructionKind::Block(instrs) => {
+                chunk.emit(Op::EnterBlock, self.span);
+                chunk.scopes.push(vec![]);
+                if instrs.is_empty() {
+                    chunk.emit(Op::Const(Value::Unit), self.span);
+                }
+                for (i, instr) in instrs.iter().enumerate() {
+                    if i > 0 {
+                        chunk.emit(Op::Pop, self.span);
+                    }
+                    instr.compile_into(chunk);
+                }
+                chunk.scopes.pop();
+                chunk.emit(Op::ExitBlock, self.span);
+            },
+            InstructionKind::IfElse{cond, cond_true, cond_false} => {
+                cond.compile_condition(chunk);
+                let jump_false = chunk.emit(Op::JumpIfFalse(0), self.span);
+                cond_true.compile_into(chunk);
+                let jump_end = chunk.emit(Op::Jump(0), self.span);
+                chunk.patch(jump_false);
+                cond_false.compile_into(chunk);
+                chunk.patch(jump_end);
+            },
+            InstructionKind::While(cond, body) => {
+                let start = chunk.ops.len();
+                cond.compile_condition(chunk);
+                let jump_end = chunk.emit(Op::JumpIfFalse(0), self.span);
+                body.compile_into(chunk);
+                chunk.emit(Op::Pop, self.span);
+                chunk.emit(Op::Jump(start), self.span);
+                chunk.patch(jump_end);
+                chunk.emit(Op::Const(Value::Unit), self.span);
+            },
+            InstructionKind::WriteAt(e1, e2) => {
+                e2.compile_moved(chunk);
+                let place = chunk.expr(e1);
+                let variable = match &e1.kind {
+                    Identifier(id) => chunk.resolve(id),
+                    _ => None,
+                };
+                match variable {
+                    Some((depth, slot)) => { chunk.emit(Op::Assign{depth, slot, expr: place}, self.span); },
+                    None => {
+                        e1.compile_place(chunk);
+                        chunk.emit(Op::Store(place), self.span);
+                    },
+                }
+            },
+            InstructionKind::Free(e) => {
+                e.compile(chunk);
+                let i = chunk.expr(e);
+                chunk.emit(Op::CheckAddress(i), e.span);
+                chunk.emit(Op::Free(i), self.span);
+            },
+            InstructionKind::FnDef(function) => { chunk.emit(Op::DefineFunction(function.clone()), self.span); },
+            InstructionKind::Return(e) => {
+                e.compile_moved(chunk);
+                chunk.emit(Op::Return, self.span);
+            },
+        }
+    }
+
+    // `scopes` : variables des NameSpaces visibles au début de l'instruction
+    pub fn compile(&self, scopes: Vec<Vec<Identifier>>) -> Chunk {
+        let mut chunk = Chunk { scopes, ..Chunk::default() };
+        self.compile_into(&mut chunk);
+        chunk
+    }
+}
+
+
+impl Function {
+    // le corps est exécuté dans le NameSpace des paramètres
+    pub fn compile(&self) -> Chunk {
+        self.body.compile(vec![self.params.iter().map(|param| param.id.clone()).collect()])
+    }
+}
@@ -1,15 +1,19 @@
 use std::fmt::{self, Display};
 
-//use crate::memory::Address; // enlever ce commentaire une fois Address défini
+use crate::memory::Address;
 use crate::parsing::expression::Expression;
+use crate::parsing::span::Span;
 use crate::identifier::Identifier;
+use crate::value::Value;
 
 use crate::parser::ParseError;
 use crate::r#type::Type;
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
     ParseError(ParseError),
+    TypeError(EvalError),
     EvalError(EvalError),
 }
 
@@ -17,6 +21,7 @@ pub enum Error {
 #[allow(dead_code)]
 pub enum EvalError {
     DivisionByZero(Expression),
+    Overflow(Expression),
     Undefined(Identifier),
     AlreadyDefined(Identifier),
     NotMutable(Option<Expression>),
@@ -24,10 +29,13 @@ pub enum EvalError {
     NonAllocatedCell(Option<Expression>),
     NonInitializedValue(Option<Expression>),
     UseAfterFree(Option<Expression>),
-//    MemoryLeak(Address), // enlever ce commentaire une fois Address défini
+    MemoryLeak(Address, Expression),
     MovedValue(Option<Expression>),
     CannotMoveOwnedValue(Option<Expression>),
     CannotFreeOwnedValue(Option<Expression>),
+    WrongArity{id: Identifier, expected: usize, found: usize},
+    Return(Value),
+    Located(Span, Box<EvalError>),
 }
 
 impl From<ParseError> for Error {
@@ -47,19 +55,23 @@ impl Display for EvalError {
         use EvalError::*;
         match self {
             DivisionByZero(e) => write!(f, "Division by zero, `{}` evaluates to 0", e),
+            Overflow(e) => write!(f, "Arithmetic overflow in `{}`", e),
             Undefined(id) => write!(f, "Undefined identifier `{}`.", id,),
             AlreadyDefined(id) => write!(f, "Identifier `{}` already defined.", id),
             NotMutable(e) => write!(f, "Cell {}is not mutable.", e.as_ref().map(|e| format!("at `{}` ", e)).unwrap_or("".to_string())),
             TypeMismatch { expression, expected, found} => {
-                write!(f, "Type mismatch in expression `{}`. Expected: {}. {}", expression, expected, found.map(|f| format!("Found: {}", f)).unwrap_or("".to_string()))
+                write!(f, "Type mismatch in expression `{}`. Expected: {}. {}", expression, expected, found.as_ref().map(|f| format!("Found: {}", f)).unwrap_or("".to_string()))
             },
             NonAllocatedCell(e) => write!(f, "Cell {}is not allocated.", e.as_ref().map(|e| format!("at `{}` ", e)).unwrap_or("".to_string())),
-            NonInitializedValue(e) => write!(f, "Value {} is not initialized.", e.as_ref().map(|e| format!("in `{}` ", e)).unwrap_or("".to_string())),
+            NonInitializedValue(e) => write!(f, "Value {}is not initialized.", e.as_ref().map(|e| format!("in `{}` ", e)).unwrap_or("".to_string())),
             UseAfterFree(e) => write!(f, "{}use after free.", e.as_ref().map(|e| format!("`{}` is a ", e)).unwrap_or("".to_string())),
-//            MemoryLeak(a) => write!(f, "leaking {}", a),
+            MemoryLeak(a, e) => write!(f, "leaking {}, allocated by `{}`", a, e),
             MovedValue(e) => write!(f, "{} has been moved", e.as_ref().map(|e| format!("`{}`", e)).unwrap_or("value".to_string())),
             CannotMoveOwnedValue(e) => write!(f, "cannot move {}, owned value with move semantics", e.as_ref().map(|e| format!("`{}`", e)).unwrap_or("this value".to_string())),
             CannotFreeOwnedValue(e) => write!(f, "cannot free {}, owned value", e.as_ref().map(|e| format!("`{}`", e)).unwrap_or("this value".to_string())),
+            WrongArity { id, expected, found } => write!(f, "function `{}` takes {} arguments but {} were given", id, expected, found),
+            Return(_) => write!(f, "`return` outside of a function"),
+            Located(_, e) => write!(f, "{}", e),
         }
     }
 }
@@ -69,6 +81,7 @@ impl Display for Error {
         use Error::*;
         match self {
             ParseError(e) => write!(f, "Parse Error: {}", e),
+            TypeError(e) => write!(f, "Type Error: {}", e),
             EvalError(e) => write!(f, "Evaluation Error: {}", e),
         }
     }
@@ -82,7 +95,38 @@ impl EvalError {
             NonAllocatedCell(None) => NonAllocatedCell(Some(e)),
             NonInitializedValue(None) => NonInitializedValue(Some(e)),
             UseAfterFree(None) => UseAfterFree(Some(e)),
+            MovedValue(None) => MovedValue(Some(e)),
             _ => self.clone(),
         }
     }
-}
\ No newline at end of file
+
+    // position de l'erreur : celle de l'expression fautive si elle est connue
+    pub fn span(&self) -> Option<Span> {
+        use EvalError::*;
+        match self {
+            Located(span, _) => Some(*span),
+            DivisionByZero(e) | Overflow(e) | TypeMismatch{expression: e, ..} | MemoryLeak(_, e) => Some(e.span),
+            NotMutable(Some(e)) | NonAllocatedCell(Some(e)) | NonInitializedValue(Some(e)) | UseAfterFree(Some(e))
+            | MovedValue(Some(e)) | CannotMoveOwnedValue(Some(e)) | CannotFreeOwnedValue(Some(e)) => Some(e.span),
+            _ => None,
+        }
+    }
+
+    // rattache au nœud `span` une erreur qui n'a pas encore de position ;
+    // une fuite est signalée après coup et `return` n'est pas une vraie erreur
+    pub fn located(self, span: Span) -> Self {
+        match self {
+            EvalError::Return(_) | EvalError::MemoryLeak(_, _) => self,
+            _ if self.span().is_some() => self,
+            _ => EvalError::Located(span, Box::new(self)),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn without_location(self) -> Self {
+        match self {
+            EvalError::Located(_, e) => *e,
+            _ => self,
+        }
+    }
+}
@@ -1,13 +1,26 @@
 use crate::identifier::Identifier;
-use crate::parsing::expression::Expression;
-use crate::parsing::instruction::Instruction;
+use crate::parsing::expression::{Expression, ExpressionKind};
+use crate::parsing::instruction::{Instruction, InstructionKind};
 use crate::value::Value;
-use Expression::*;
+use ExpressionKind::*;
 use crate::parsing::binop::Binop;
 use crate::error::EvalError;
 use crate::namespace::NameSpace;
 use crate::namespacestack::NameSpaceStack;
 use crate::r#type::Type;
+use crate::memory::Address;
+
+// `None` si le résultat sort des `isize`, comme `MIN / -1`
+pub fn arithmetic(op: Binop, v1: isize, v2: isize) -> Option<isize> {
+    match op {
+        Binop::Add => v1.checked_add(v2),
+        Binop::Sub => v1.checked_sub(v2),
+        Binop::Mul => v1.checked_mul(v2),
+        Binop::Div => v1.checked_div(v2),
+        Binop::Mod => v1.checked_rem(v2),
+        _ => unreachable!("{} is not an arithmetic operator", op),
+    }
+}
 
 
 impl Expression {
@@ -21,21 +34,148 @@ impl Expression {
             found: Some(Type::from(&v))})
     }
 
+    fn eval_and_cast_to_bool(&self, nss: &mut NameSpaceStack) -> Result<bool, EvalError> {
+        let v = self.eval(nss)?;
+        v.to_bool()
+         .map_err(|_| EvalError::TypeMismatch{
+            expression: self.clone(),
+            expected: Type::Bool,
+            found: Some(Type::from(&v))})
+    }
+
+    fn eval_and_cast_to_address(&self, nss: &mut NameSpaceStack) -> Result<Address, EvalError> {
+        let v = self.eval(nss)?;
+        self.cast_to_address(&v)
+    }
+
+    // `v` est la valeur de `self`, déjà évaluée
+    fn cast_to_address(&self, v: &Value) -> Result<Address, EvalError> {
+        v.to_address()
+         .map_err(|_| EvalError::TypeMismatch{
+            expression: self.clone(),
+            expected: Type::Pointer(Box::new(Type::Unknown)),
+            found: Some(Type::from(v))})
+    }
+
+    // adresse de la cellule désignée par une expression gauche (`x`, `*e`)
+    fn eval_to_address(&self, nss: &mut NameSpaceStack) -> Result<Address, EvalError> {
+        match &self.kind {
+            Identifier(id) => nss.get_address(id).map_err(|err| err.located(self.span)),
+            Deref(e) => e.eval_and_cast_to_address(nss),
+            _ => Err(EvalError::NonAllocatedCell(Some(self.clone()))),
+        }
+    }
+
+    // une valeur possédée ne peut être déplacée que depuis son propriétaire :
+    // une variable, ou `*e` où `e` est lui-même propriétaire d'un pointeur
+    fn is_owner(&self, nss: &mut NameSpaceStack) -> Result<bool, EvalError> {
+        match &self.kind {
+            Identifier(_) => Ok(true),
+            Deref(e) => Ok(e.is_owner(nss)? && e.eval(nss)?.is_owned()),
+            _ => Ok(false),
+        }
+    }
+
+    // évaluation en position de move (`let x = e`, `e1 = e`, `free(e)`)
+    fn eval_and_move(&self, nss: &mut NameSpaceStack) -> Result<Value, EvalError> {
+        let value = self.eval(nss)?;
+        self.move_value(value, nss)
+    }
+
+    // `value` vient d'être obtenue en évaluant `self` : si elle est possédée,
+    // la cellule d'où elle provient est marquée comme déplacée
+    fn move_value(&self, value: Value, nss: &mut NameSpaceStack) -> Result<Value, EvalError> {
+        match &self.kind {
+            Identifier(_) | Deref(_) if value.is_owned() => {
+                if !self.is_owner(nss)? {
+                    return Err(EvalError::CannotMoveOwnedValue(Some(self.clone())));
+                }
+                let address = self.eval_to_address(nss)?;
+                nss.move_out(&address).map_err(|err| err.with_expression_info(self.clone()))?;
+                Ok(value)
+            },
+            _ => Ok(value),
+        }
+    }
+
     pub fn eval(&self, nss: &mut NameSpaceStack) -> Result<Value, EvalError> {
+        self.eval_kind(nss).map_err(|err| err.located(self.span))
+    }
 
-        match self {
+    fn eval_kind(&self, nss: &mut NameSpaceStack) -> Result<Value, EvalError> {
+        match &self.kind {
             Const(v) => Ok(Value::from(*v)),
-            Identifier(id) => todo!(),
-            BinOp(e1, Binop::Add, e2) => {
+            Identifier(id) => nss.find(id).map_err(|err| err.with_expression_info(self.clone())),
+            BinOp(e1, op @ (Binop::Add | Binop::Sub | Binop::Mul | Binop::Div | Binop::Mod), e2) => {
                 let v1 = e1.eval_and_cast_to_int(nss)?;
                 let v2 = e2.eval_and_cast_to_int(nss)?;
-                Ok(Value::Integer(v1 + v2))
+                if v2 == 0 && matches!(op, Binop::Div | Binop::Mod) {
+                    return Err(EvalError::DivisionByZero(*e2.clone()));
+                }
+                arithmetic(*op, v1, v2).map(Value::Integer).ok_or_else(|| EvalError::Overflow(self.clone()))
+            },
+            BinOp(e1, op @ (Binop::Leq | Binop::Geq | Binop::Lt | Binop::Gt), e2) => {
+                let v1 = e1.eval_and_cast_to_int(nss)?;
+                let v2 = e2.eval_and_cast_to_int(nss)?;
+                match op {
+                    Binop::Leq => Ok(Value::Boolean(v1 <= v2)),
+                    Binop::Geq => Ok(Value::Boolean(v1 >= v2)),
+                    Binop::Lt => Ok(Value::Boolean(v1 < v2)),
+                    _ => Ok(Value::Boolean(v1 > v2)),
+                }
+            },
+            BinOp(e1, op @ (Binop::Eq | Binop::Neq), e2) => {
+                let v1 = e1.eval(nss)?;
+                let v2 = e2.eval(nss)?;
+                Ok(Value::Boolean((v1 == v2) == (*op == Binop::Eq)))
+            },
+            BinOp(e1, op @ (Binop::And | Binop::Or), e2) => {
+                let v1 = e1.eval_and_cast_to_bool(nss)?;
+                let v2 = e2.eval_and_cast_to_bool(nss)?;
+                match op {
+                    Binop::And => Ok(Value::Boolean(v1 && v2)),
+                    _ => Ok(Value::Boolean(v1 || v2)),
+                }
+            },
+            Conditional{cond, cond_true, cond_false} => {
+                if cond.eval_and_cast_to_bool(nss)? {
+                    cond_true.eval(nss)
+                } else {
+                    cond_false.eval(nss)
+                }
+            },
+            NewPtr => Ok(Value::Pointer(nss.malloc(self.clone()))),
+            Deref(e) => {
+                let address = e.eval_and_cast_to_address(nss)?;
+                nss.read_at(&address).map_err(|err| err.with_expression_info(self.clone()))
+            },
+            AmpersAnd(e) => Ok(Value::Pointer(e.eval_to_address(nss)?)),
+            Call(id, args) => {
+                let function = nss.function(id)?;
+                if args.len() != function.params.len() {
+                    return Err(EvalError::WrongArity{id: id.clone(), expected: function.params.len(), found: args.len()});
+                }
+                // les arguments déjà évalués restent atteignables pendant
+                // l'évaluation des suivants, qui peuvent appeler des fonctions
+                let mut values = vec![];
+                for arg in args {
+                    let value = arg.eval_and_move(nss).inspect_err(|_| nss.release(values.len()))?;
+                    nss.hold(value.clone());
+                    values.push(value);
+                }
+                nss.release(values.len());
+                // l'appelé ne voit que ses paramètres
+                nss.push_frame(NameSpace::new());
+                let result = function.params.iter().zip(values)
+                    .try_for_each(|(param, value)| nss.declare(&param.id, param.mutable, value))
+                    .and_then(|_| match function.body.exec(nss) {
+                        Ok((_, value)) | Err(EvalError::Return(value)) => Ok(value),
+                        Err(err) => Err(err),
+                    })
+                    .map(|value| if function.ret == Type::Unit { Value::Unit } else { value });
+                nss.pop_frame(result.as_ref().unwrap_or(&Value::Unit));
+                result
             },
-            BinOp(_,_,_) => todo!(),
-            Conditional{cond, cond_true, cond_false} => todo!(),
-            NewPtr => todo!(),
-            Deref(_) => todo!(),
-            AmpersAnd(_) => todo!(),
         }
     }
 }
@@ -43,31 +183,64 @@ impl Expression {
 
 impl Instruction {
     pub fn exec(&self, nss: &mut NameSpaceStack) -> Result<(Option<Identifier>, Value), EvalError> {
-        match self {
-            Instruction::Let{id, mutable, expr} => {
-                let entier = expr.eval(nss)?;
-                let ismut = mutable;
-                let id = &Identifier::from(id.as_str());
-                let _def = nss.declare(id, *ismut, entier);
-                Ok((Some(id.clone()), entier.clone()))
+        self.exec_kind(nss).map_err(|err| err.located(self.span))
+    }
+
+    fn exec_kind(&self, nss: &mut NameSpaceStack) -> Result<(Option<Identifier>, Value), EvalError> {
+        match &self.kind {
+            InstructionKind::Let{id, mutable, expr} => {
+                let value = expr.eval_and_move(nss)?;
+                nss.declare(id, *mutable, value.clone())?;
+                Ok((Some(id.clone()), value))
             },
-            Instruction::Expr(expr) => {
+            InstructionKind::Expr(expr) => {
                 Ok((None, expr.eval(nss)?))
             }
-            Instruction::Block(instrs) => {
+            InstructionKind::Block(instrs) => {
                 nss.push(NameSpace::new());
                 let mut return_value = Value::Unit;
                 for instr in instrs {
-                    let (_id, val) = instr.exec(nss).map_err(|err| {nss.pop(); err})?;
+                    let (_id, val) = instr.exec(nss).inspect_err(|err| match err {
+                        EvalError::Return(value) => {nss.pop_keeping(value);},
+                        _ => {nss.pop();},
+                    })?;
                     return_value = val;
                 };
-                nss.pop();
+                nss.pop_keeping(&return_value);
                 Ok((None, return_value))
             }
-            Instruction::IfElse { cond, cond_true, cond_false } => todo!(),
-            Instruction::While(e, instr) => todo!(),
-            Instruction::WriteAt(e1, e2) => todo!(),
-            Instruction::Free(e) => todo!(),
+            InstructionKind::IfElse{cond, cond_true, cond_false} => {
+                if cond.eval_and_cast_to_bool(nss)? {
+                    cond_true.exec(nss)
+                } else {
+                    cond_false.exec(nss)
+                }
+            },
+            InstructionKind::While(cond, body) => {
+                while cond.eval_and_cast_to_bool(nss)? {
+                    body.exec(nss)?;
+                }
+                Ok((None, Value::Unit))
+            },
+            InstructionKind::WriteAt(e1, e2) => {
+                let value = e2.eval_and_move(nss)?;
+                let address = e1.eval_to_address(nss)?;
+                nss.write_at(&address, value).map_err(|err| err.with_expression_info(e1.clone()))?;
+                Ok((None, Value::Unit))
+            },
+            InstructionKind::Free(e) => {
+                // libérer ne déplace pas le pointeur : c'est la cellule libérée
+                // qui détecte ensuite les accès et les doubles libérations
+                let value = e.eval(nss)?;
+                let address = e.cast_to_address(&value)?;
+                nss.free(&address).map_err(|err| err.with_expression_info(e.clone()))?;
+                Ok((None, Value::Unit))
+            },
+            InstructionKind::FnDef(function) => {
+                nss.define_function(function.clone())?;
+                Ok((Some(function.id.clone()), Value::Unit))
+            },
+            InstructionKind::Return(e) => Err(EvalError::Return(e.eval_and_move(nss)?)),
         }
     }
 }
\ No newline at end of file
@@ -9,19 +9,43 @@ mod namespacestack;
 mod memorycell;
 mod eval;
 mod r#type;
+mod memory;
+mod typecheck;
+mod pretty;
+mod bytecode;
+mod vm;
+#[cfg(test)]
+mod test_memory;
+#[cfg(test)]
+mod test_typecheck;
+#[cfg(test)]
+mod test_function;
+#[cfg(test)]
+mod test_parser;
+#[cfg(test)]
+mod test_repl;
+#[cfg(test)]
+mod test_pretty;
+#[cfg(test)]
+mod test_vm;
 
 
 // LISTE DES IMPORTS
 use std::io::{self, BufRead, Write};
+use std::{env, fs, process};
 use identifier::Identifier;
+use parsing::expression::Expression;
 use parsing::instruction::Instruction;
 use namespace::NameSpace;
 use namespacestack::NameSpaceStack;
 use value::Value;
-use crate::parser::Parse;
-use parsing::expression;
-use crate::error::Error;
+use crate::parser::{Parse, ParseError};
+use crate::error::{Error, EvalError};
 use crate::r#type::Type;
+use crate::typecheck::TypeEnv;
+
+// EVALUATEUR DES INSTRUCTIONS : PARCOURS DE L'ARBRE, OU MACHINE A PILE AVEC `--vm`
+type Exec = fn(&Instruction, &mut NameSpaceStack) -> Result<(Option<Identifier>, Value), EvalError>;
 
 // AFFICHAGE DU PROMPT
 fn prompt() {
@@ -29,6 +53,40 @@ fn prompt() {
     io::stdout().flush().unwrap();
 }
 
+// PROMPT SECONDAIRE, QUAND L'INSTRUCTION CONTINUE SUR LA LIGNE SUIVANTE
+fn continuation_prompt() {
+    print!("    ... ");
+    io::stdout().flush().unwrap();
+}
+
+// une accolade ou un commentaire reste ouvert, ou l'analyse échoue sur la fin de l'entrée ;
+// les accolades ne sont comptées que hors des commentaires
+fn is_incomplete(input: &str) -> bool {
+    let (mut braces, mut comments) = (0isize, 0usize);
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('/', Some('*')) => { chars.next(); comments += 1; },
+            ('*', Some('/')) if comments > 0 => { chars.next(); comments -= 1; },
+            _ if comments > 0 => (),
+            ('/', Some('/')) => { chars.find(|c| *c == '\n'); },
+            ('{', _) => braces += 1,
+            ('}', _) => braces -= 1,
+            _ => (),
+        }
+    }
+    if braces > 0 || comments > 0 {
+        return true;
+    }
+    match Instruction::parse(input) {
+        Err(ParseError::CannotParse{line, column, ..}) => {
+            let last = input.lines().last().unwrap_or("");
+            line == input.lines().count() && column > last.chars().count()
+        },
+        _ => false,
+    }
+}
+
 
 // fn parse_eval(input: &str, ns: &NameSpace) -> Result<isize, Error> {
 //     match Expression::parse(input) {
@@ -37,31 +95,259 @@ fn prompt() {
 //     }
 // }
 
-fn parse_exec(input: &str, nss: &mut NameSpaceStack) -> Result<(Option<Identifier>, Value), Error> {
-    match Instruction::parse(input) {
-        Ok(instr) => {
-            instr.exec(nss).map_err(|err| Error::EvalError(err))
+// l'instruction n'est exécutée que si elle est bien typée ; l'environnement
+// de typage n'est mis à jour que si l'exécution réussit
+// `offset` est la position de `input` dans le texte de la session
+fn parse_exec(input: &str, offset: usize, source: &str, env: &mut TypeEnv, nss: &mut NameSpaceStack, exec: Exec) -> Result<(Option<Identifier>, Type, Value), Vec<Error>> {
+    let mut instr = Instruction::parse(input).map_err(|e| match e {
+        ParseError::CannotParse{line, column, fragment, expected} => {
+            let line = line + source[..offset].matches('\n').count();
+            vec![Error::ParseError(ParseError::CannotParse{line, column, fragment, expected})]
+        },
+        e => vec![Error::ParseError(e)],
+    })?;
+    instr.shift_spans(offset);
+    let mut checked_env = env.clone();
+    let t = instr.typecheck(&mut checked_env);
+    let errors = checked_env.take_errors();
+    if !errors.is_empty() {
+        return Err(errors.into_iter().map(Error::TypeError).collect());
+    }
+    let res = exec(&instr, nss).map_err(|e| vec![Error::EvalError(e)]);
+    // la valeur d'une instruction qui n'est pas un `let` est perdue
+    nss.check_leaks(&Value::Unit);
+    let (id, val) = res?;
+    let t = checked_env.resolve(&t);
+    *env = checked_env;
+    Ok((id, t, val))
+}
+
+// AFFICHAGE D'UNE ERREUR, AVEC LA LIGNE FAUTIVE SOULIGNEE
+fn report_error(out: &mut impl Write, source: &str, e: &Error) {
+    writeln!(out, "{}", e).unwrap();
+    let (line, column, width) = match e {
+        Error::ParseError(ParseError::CannotParse{line, column, ..}) => (*line, *column, 1),
+        Error::TypeError(e) | Error::EvalError(e) => match e.span() {
+            Some(span) => {
+                let (line, column) = span.line_col(source);
+                writeln!(out, " --> line {}, column {}", line, column).unwrap();
+                let text = source.get(span.start..span.end).unwrap_or("");
+                (line, column, text.lines().next().unwrap_or("").chars().count().max(1))
+            },
+            None => return,
+        },
+        _ => return,
+    };
+    let text = source.lines().nth(line - 1).unwrap_or("");
+    let margin = " ".repeat(line.to_string().len());
+    writeln!(out, "{} | {}", line, text).unwrap();
+    writeln!(out, "{} | {}{}", margin, " ".repeat(column - 1), "^".repeat(width)).unwrap();
+}
+
+// AFFICHAGE DES FUITES MEMOIRE, RENVOIE FAUX S'IL Y EN A
+fn report_leaks(out: &mut impl Write, source: &str, nss: &mut NameSpaceStack) -> bool {
+    let leaks = nss.take_leaks();
+    for leak in &leaks {
+        report_error(out, source, &Error::EvalError(leak.clone()));
+    }
+    leaks.is_empty()
+}
+
+// EXECUTION D'UN FICHIER : RENVOIE LE CODE DE SORTIE DU PROGRAMME
+fn run(path: &str, exec: Exec) -> i32 {
+    let mut err = io::stderr();
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("Cannot read `{}`: {}", path, e);
+            return 1;
+        }
+    };
+    let instrs = match Vec::<Instruction>::parse(&source) {
+        Ok(instrs) => instrs,
+        Err(e) => {
+            report_error(&mut err, &source, &Error::ParseError(e));
+            return 1;
+        }
+    };
+    // tout le programme est vérifié avant d'exécuter la première instruction
+    let mut env = TypeEnv::new();
+    for instr in &instrs {
+        instr.typecheck(&mut env);
+    }
+    let errors = env.take_errors();
+    for e in &errors {
+        report_error(&mut err, &source, &Error::TypeError(e.clone()));
+    }
+    if !errors.is_empty() {
+        return 1;
+    }
+    let mut nss = NameSpaceStack::new();
+    nss.push(NameSpace::new());
+    let mut status = 0;
+    for instr in &instrs {
+        if let Err(e) = exec(instr, &mut nss) {
+            report_error(&mut err, &source, &Error::EvalError(e));
+            status = 1;
+            break;
+        }
+        nss.check_leaks(&Value::Unit);
+    }
+    nss.pop();
+    if !report_leaks(&mut err, &source, &mut nss) {
+        status = 1;
+    }
+    status
+}
+
+// COMMANDES DE LA BOUCLE INTERACTIVE (`:env`, `:heap`, `:type`, `:ast`, `:reset`)
+fn meta_command(out: &mut impl Write, command: &str, source: &mut String, env: &mut TypeEnv, nss: &mut NameSpaceStack) {
+    let (name, arg) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+    let arg = arg.trim();
+    match (name, arg.is_empty()) {
+        (":env", true) => {
+            for (depth, ns) in nss.namespaces() {
+                for (id, cell) in ns.bindings() {
+                    let t = env.type_of(id)
+                        .or_else(|| cell.get_value().ok().map(|value| Type::from(&value)))
+                        .unwrap_or(Type::Unknown);
+                    let mutable = if cell.is_mutable() { "mut " } else { "" };
+                    writeln!(out, "[{}] {}{} : {} = {}", depth, mutable, id, t, cell).unwrap();
+                }
+            }
+        },
+        (":heap", true) => {
+            for (i, cell, origin) in nss.heap_cells() {
+                writeln!(out, "@heap:{} = {} (allocated by `{}`)", i, cell, origin).unwrap();
+            }
+        },
+        // le typage se fait sur une copie : rien n'est évalué ni déclaré
+        (":type", false) => match Expression::parse(arg) {
+            Ok(expr) => {
+                let mut checked_env = env.clone();
+                let t = expr.typecheck(&mut checked_env);
+                let errors = checked_env.take_errors();
+                for e in &errors {
+                    report_error(out, arg, &Error::TypeError(e.clone()));
+                }
+                if errors.is_empty() {
+                    writeln!(out, "{}", checked_env.resolve(&t)).unwrap();
+                }
+            },
+            Err(e) => report_error(out, arg, &Error::ParseError(e)),
+        },
+        (":ast", false) => match Instruction::parse(arg) {
+            Ok(instr) => writeln!(out, "{:#?}", instr).unwrap(),
+            Err(e) => report_error(out, arg, &Error::ParseError(e)),
+        },
+        (":reset", true) => {
+            *env = TypeEnv::new();
+            *nss = NameSpaceStack::new();
+            nss.push(NameSpace::new());
+            source.clear();
+        },
+        _ => writeln!(out, "usage: :env | :heap | :type <expr> | :ast <instr> | :reset").unwrap(),
+    }
+}
+
+// MISE EN FORME D'UN FICHIER, ECRITE SUR LA SORTIE STANDARD
+fn fmt(path: &str) -> i32 {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("Cannot read `{}`: {}", path, e);
+            return 1;
+        }
+    };
+    match Vec::<Instruction>::parse(&source) {
+        Ok(instrs) => {
+            print!("{}", pretty::pretty_program(&instrs, &source));
+            0
+        },
+        Err(e) => {
+            report_error(&mut io::stderr(), &source, &Error::ParseError(e));
+            1
         }
-        Err(e) => Err(Error::ParseError(e)),
     }
 }
 
 // FONCTION PRINCIPALE
 fn main(){
+    let mut args: Vec<String> = env::args().collect();
+    let exec: Exec = match args.iter().position(|arg| arg == "--vm") {
+        Some(i) => {
+            args.remove(i);
+            Instruction::exec_bytecode
+        },
+        None => Instruction::exec,
+    };
+    match args.get(1).map(|arg| arg.as_str()) {
+        Some(command @ ("run" | "fmt")) => match args.get(2) {
+            Some(path) if command == "run" => process::exit(run(path, exec)),
+            Some(path) => process::exit(fmt(path)),
+            None => {
+                eprintln!("usage: {} {} <file.mrs>", args[0], command);
+                process::exit(2);
+            }
+        },
+        Some(_) => {
+            eprintln!("usage: {} [--vm] [run <file.mrs> | fmt <file.mrs>]", args[0]);
+            process::exit(2);
+        },
+        None => repl(exec),
+    }
+}
+
+// BOUCLE INTERACTIVE
+fn repl(exec: Exec){
     prompt();
+    let mut out = io::stdout();
+    let mut env = TypeEnv::new();
     let mut nss = NameSpaceStack::new();
     nss.push(NameSpace::new());
+    // toutes les entrées lues, pour citer la source des erreurs
+    let mut source = String::new();
+    // l'instruction en cours de saisie, sur une ou plusieurs lignes
+    let mut input = String::new();
     let stdin = io::stdin().lock();
     for line in stdin.lines() {
         let line = line.unwrap();
-        match parse_exec(&line, &mut nss) {
-            Ok((id, val)) => {
-                println!("{} : {} = {}", id.unwrap_or(Identifier::from("-")), Type::from(&val), val);
+        if line.trim().is_empty() {
+            // une ligne vide abandonne la saisie en cours
+            input.clear();
+            prompt();
+            continue;
+        }
+        if input.is_empty() && line.trim_start().starts_with(':') {
+            meta_command(&mut out, line.trim(), &mut source, &mut env, &mut nss);
+            prompt();
+            continue;
+        }
+        if !input.is_empty() {
+            input.push('\n');
+        }
+        input.push_str(&line);
+        if is_incomplete(&input) {
+            continuation_prompt();
+            continue;
+        }
+        let offset = source.len();
+        source.push_str(&input);
+        source.push('\n');
+        match parse_exec(&input, offset, &source, &mut env, &mut nss, exec) {
+            Ok((id, t, val)) => {
+                println!("{} : {} = {}", id.unwrap_or(Identifier::from("-")), t, val);
             }
-            Err(e) => {
-                println!("{}", e);
+            Err(errors) => {
+                for e in errors {
+                    report_error(&mut out, &source, &e);
+                }
             }
         }
+        report_leaks(&mut out, &source, &mut nss);
+        input.clear();
         prompt();
     }
+    nss.pop();
+    report_leaks(&mut out, &source, &mut nss);
 }
@@ -0,0 +1,104 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::{self, Display};
+
+use crate::{error::EvalError, identifier::Identifier, value::Value};
+use crate::parsing::expression::Expression;
+use crate::memorycell::MemoryCell;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Address {
+    StackAddress(usize, Identifier),
+    HeapAddress(usize),
+}
+
+impl Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Address::*;
+        match self {
+            StackAddress(ns_id, id) => write!(f, "@{}:{}", ns_id, id),
+            HeapAddress(i) => write!(f, "@heap:{}", i),
+        }
+    }
+}
+
+// le tas : une cellule à `None` a été libérée ; son indice n'est jamais
+// réutilisé, pour détecter les accès après libération
+// `origins[i]` est l'expression qui a alloué la cellule `i`
+// `live` contient les indices des cellules non libérées
+#[derive(Debug)]
+pub struct Memory {
+    cells: Vec<Option<MemoryCell>>,
+    origins: Vec<Expression>,
+    live: BTreeSet<usize>,
+}
+
+impl Memory {
+    pub fn new() -> Self {
+        Memory { cells: vec![], origins: vec![], live: BTreeSet::new() }
+    }
+
+    pub fn malloc(&mut self, origin: Expression) -> Address {
+        self.cells.push(Some(MemoryCell::new_uninitialized(true)));
+        self.origins.push(origin);
+        self.live.insert(self.cells.len() - 1);
+        Address::HeapAddress(self.cells.len() - 1)
+    }
+
+    fn get(&self, i: usize) -> Result<&MemoryCell, EvalError> {
+        match self.cells.get(i) {
+            Some(Some(cell)) => Ok(cell),
+            Some(None) => Err(EvalError::UseAfterFree(None)),
+            None => Err(EvalError::NonAllocatedCell(None)),
+        }
+    }
+
+    fn get_mut(&mut self, i: usize) -> Result<&mut MemoryCell, EvalError> {
+        match self.cells.get_mut(i) {
+            Some(Some(cell)) => Ok(cell),
+            Some(None) => Err(EvalError::UseAfterFree(None)),
+            None => Err(EvalError::NonAllocatedCell(None)),
+        }
+    }
+
+    pub fn read(&self, i: usize) -> Result<Value, EvalError> {
+        self.get(i)?.get_value()
+    }
+
+    pub fn write(&mut self, i: usize, value: Value) -> Result<(), EvalError> {
+        self.get_mut(i)?.set_value(value)
+    }
+
+    pub fn move_out(&mut self, i: usize) -> Result<(), EvalError> {
+        self.get_mut(i)?.move_out()
+    }
+
+    pub fn free(&mut self, i: usize) -> Result<(), EvalError> {
+        self.get(i)?;
+        self.cells[i] = None;
+        self.live.remove(&i);
+        Ok(())
+    }
+
+    // aucune cellule n'est vivante
+    pub fn is_empty(&self) -> bool {
+        self.live.is_empty()
+    }
+
+    // cellules non libérées, avec l'expression qui les a allouées
+    pub fn cells(&self) -> impl Iterator<Item = (usize, &MemoryCell, &Expression)> {
+        self.live.iter().filter_map(|i| self.cells[*i].as_ref().map(|cell| (*i, cell, &self.origins[*i])))
+    }
+
+    // libère toutes les cellules vivantes qui ne sont pas dans `reachable`
+    // et renvoie une erreur `MemoryLeak` pour chacune d'elles
+    pub fn collect_leaks(&mut self, reachable: &HashSet<usize>) -> Vec<EvalError> {
+        let leaked: Vec<usize> = self.live.iter().filter(|i| !reachable.contains(i)).copied().collect();
+        for i in &leaked {
+            self.cells[*i] = None;
+            self.live.remove(i);
+        }
+        leaked.into_iter()
+            .map(|i| EvalError::MemoryLeak(Address::HeapAddress(i), self.origins[i].clone()))
+            .collect()
+    }
+}
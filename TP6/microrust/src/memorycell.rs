@@ -4,14 +4,24 @@ use crate::{error::EvalError, value::Value};
 
 pub struct MemoryCell {
     mutable: bool,
-    value:  Value,
+    moved: bool,
+    value:  Option<Value>,
 }
 
 impl MemoryCell {
     pub fn new(mutable: bool, value: Value) -> MemoryCell {
         MemoryCell {
             mutable,
-            value,
+            moved: false,
+            value: Some(value),
+        }
+    }
+
+    pub fn new_uninitialized(mutable: bool) -> MemoryCell {
+        MemoryCell {
+            mutable,
+            moved: false,
+            value: None,
         }
     }
 
@@ -20,11 +30,36 @@ impl MemoryCell {
     }
 
     pub fn get_value(&self) -> Result<Value, EvalError>{
-        Ok(self.value.clone())
+        if self.moved {
+            return Err(EvalError::MovedValue(None));
+        }
+        self.value.clone().ok_or(EvalError::NonInitializedValue(None))
     }
 
     pub fn set_value(&mut self, value: Value) -> Result<(), EvalError> {
-        if (&self).is_mutable() {Ok(self.value = value)}
+        if self.is_mutable() {
+            self.value = Some(value);
+            self.moved = false;
+            Ok(())
+        }
         else {Err(EvalError::NotMutable(None))}
     }
-}
\ No newline at end of file
+
+    pub fn move_out(&mut self) -> Result<(), EvalError> {
+        self.get_value()?;
+        self.moved = true;
+        Ok(())
+    }
+}
+
+use std::fmt;
+
+impl fmt::Display for MemoryCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.value, self.moved) {
+            (_, true) => write!(f, "<moved>"),
+            (Some(value), false) => write!(f, "{}", value),
+            (None, false) => write!(f, "<uninitialized>"),
+        }
+    }
+}
@@ -1,47 +1,89 @@
 use std::collections::HashMap;
-use std::mem;
-use crate::{error::EvalError, identifier::Identifier, parsing::expression::Expression, value::Value};
+use crate::{error::EvalError, identifier::Identifier, value::Value};
 use crate::memorycell::MemoryCell;
 
-#[derive(Debug)]
-pub struct NameSpace(HashMap<Identifier, MemoryCell>);
+// les cellules sont rangées dans l'ordre des déclarations : le bytecode
+// désigne une variable par son indice (`slot`) dans le NameSpace
+#[derive(Debug, Default)]
+pub struct NameSpace {
+    slots: HashMap<Identifier, usize>,
+    cells: Vec<(Identifier, MemoryCell)>,
+}
 
 
 
 impl NameSpace {
     pub fn new() -> Self {
-        NameSpace(HashMap::new())
+        NameSpace { slots: HashMap::new(), cells: vec![] }
     }
 
     pub fn declare(&mut self, id: &Identifier, memcell: MemoryCell) -> Result<(), EvalError> {
 //        self.0.try_insert(id, value).map_err(|_| EvalError::AlreadyDefined(id))
-        if self.0.contains_key(&id) {
+        if self.slots.contains_key(id) {
             Err(EvalError::AlreadyDefined(id.clone()))
         } else {
-            self.0.insert(id.clone(), memcell);
+            self.slots.insert(id.clone(), self.cells.len());
+            self.cells.push((id.clone(), memcell));
             Ok(())
         }
     }
 
     pub fn find(&self, id: &Identifier) -> Result<Value, EvalError> {
-        match self.0.get(id) {
-            Some(MemoryCell) => MemoryCell.get_value(),
+        match self.slots.get(id) {
+            Some(slot) => self.find_slot(*slot),
             None => Err(EvalError::Undefined(id.clone())),
         }
     }
 
+    pub fn find_slot(&self, slot: usize) -> Result<Value, EvalError> {
+        self.cells[slot].1.get_value()
+    }
+
     pub fn set(&mut self, id: &Identifier, value: Value) -> Result<(), EvalError> {
-        match self.0.get_mut(id) {
-            Some(MemoryCell) => {
-                MemoryCell.set_value(value)
-            },
+        match self.slots.get(id) {
+            Some(slot) => self.set_slot(*slot, value),
+            None => Err(EvalError::Undefined(id.clone())),
+        }
+    }
+
+    pub fn set_slot(&mut self, slot: usize, value: Value) -> Result<(), EvalError> {
+        self.cells[slot].1.set_value(value)
+    }
+
+    pub fn move_out(&mut self, id: &Identifier) -> Result<(), EvalError> {
+        match self.slots.get(id) {
+            Some(slot) => self.move_slot(*slot),
             None => Err(EvalError::Undefined(id.clone())),
         }
     }
+
+    pub fn move_slot(&mut self, slot: usize) -> Result<(), EvalError> {
+        self.cells[slot].1.move_out()
+    }
+
+    pub fn contains_key(&self, id: &Identifier) -> bool {
+        self.slots.contains_key(id)
+    }
+
+    // les variables du NameSpace, dans l'ordre de leurs indices
+    pub fn identifiers(&self) -> impl Iterator<Item = &Identifier> {
+        self.cells.iter().map(|(id, _)| id)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = Value> + '_ {
+        self.cells.iter().filter_map(|(_, memory_cell)| memory_cell.get_value().ok())
+    }
+
+    // les variables du NameSpace, triées par nom
+    pub fn bindings(&self) -> Vec<(&Identifier, &MemoryCell)> {
+        let mut bindings: Vec<_> = self.cells.iter().map(|(id, memory_cell)| (id, memory_cell)).collect();
+        bindings.sort_by_key(|(id, _)| id.to_string());
+        bindings
+    }
 }
 
 
-/* 
+/*
 #[cfg(test)]
 mod test_namespace {
 
@@ -1,21 +1,133 @@
 use crate::{error::EvalError, identifier::Identifier, namespace::NameSpace, value::Value};
+use crate::bytecode::Chunk;
 use crate::memorycell::MemoryCell;
+use crate::memory::{Address, Memory};
+use crate::parsing::expression::Expression;
+use crate::parsing::function::Function;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 
+// `frames` contient l'indice du premier NameSpace de chaque appel de
+// fonction en cours : un appel ne voit pas les variables de l'appelant
+// `held` contient les arguments déjà évalués des appels en cours de préparation
+// `ids[i]` identifie de façon unique le i-ème NameSpace empilé : une adresse
+// de pile dont le NameSpace a été dépilé ne désigne plus aucune cellule
 pub struct NameSpaceStack {
     stack: Vec<NameSpace>,
+    ids: Vec<usize>,
+    next_id: usize,
+    frames: Vec<usize>,
+    held: Vec<Value>,
+    functions: HashMap<Identifier, Rc<Function>>,
+    // corps des fonctions déjà compilés pour la machine à pile
+    chunks: HashMap<Identifier, Rc<Chunk>>,
+    heap: Memory,
+    leaks: Vec<EvalError>,
 }
 
 impl NameSpaceStack {
     pub fn new() -> Self {
-        NameSpaceStack { stack: vec![] }
+        NameSpaceStack { stack: vec![], ids: vec![], next_id: 0, frames: vec![], held: vec![], functions: HashMap::new(), chunks: HashMap::new(), heap: Memory::new(), leaks: vec![] }
     }
 
     pub fn push(&mut self, ns: NameSpace) {
         self.stack.push(ns);
+        self.ids.push(self.next_id);
+        self.next_id += 1;
     }
 
+    // les cellules du tas qui ne sont plus atteignables une fois le
+    // NameSpace dépilé sont signalées comme fuites
     pub fn pop(&mut self) -> Option<NameSpace> {
-        self.stack.pop()
+        self.pop_keeping(&Value::Unit)
+    }
+
+    // comme `pop`, mais `value` (le résultat du bloc) reste atteignable
+    pub fn pop_keeping(&mut self, value: &Value) -> Option<NameSpace> {
+        let ns = self.stack.pop();
+        self.ids.pop();
+        self.check_leaks(value);
+        ns
+    }
+
+    pub fn push_frame(&mut self, ns: NameSpace) {
+        self.frames.push(self.stack.len());
+        self.push(ns);
+    }
+
+    pub fn pop_frame(&mut self, value: &Value) {
+        while self.stack.len() > self.frame_base() {
+            self.stack.pop();
+            self.ids.pop();
+        }
+        self.frames.pop();
+        self.check_leaks(value);
+    }
+
+    // `value` n'est stockée nulle part mais doit rester atteignable jusqu'au `release`
+    pub fn hold(&mut self, value: Value) {
+        self.held.push(value);
+    }
+
+    // les `n` dernières valeurs retenues ne sont plus des racines
+    pub fn release(&mut self, n: usize) {
+        self.held.truncate(self.held.len() - n);
+    }
+
+    fn frame_base(&self) -> usize {
+        self.frames.last().copied().unwrap_or(0)
+    }
+
+    pub fn define_function(&mut self, function: Rc<Function>) -> Result<(), EvalError> {
+        if self.functions.contains_key(&function.id) {
+            return Err(EvalError::AlreadyDefined(function.id.clone()));
+        }
+        self.functions.insert(function.id.clone(), function);
+        Ok(())
+    }
+
+    pub fn function(&self, id: &Identifier) -> Result<Rc<Function>, EvalError> {
+        self.functions.get(id).cloned().ok_or(EvalError::Undefined(id.clone()))
+    }
+
+    pub fn chunks(&mut self) -> &mut HashMap<Identifier, Rc<Chunk>> {
+        &mut self.chunks
+    }
+
+    // `value` reste atteignable même s'il n'est stocké dans aucune variable
+    pub fn check_leaks(&mut self, value: &Value) {
+        // cas courant dans les boucles : rien n'est alloué sur le tas
+        if self.heap.is_empty() {
+            return;
+        }
+        let mut reachable = HashSet::new();
+        let mut todo: Vec<Value> = self.stack.iter().flat_map(|ns| ns.values()).collect();
+        todo.extend(self.held.iter().cloned());
+        todo.push(value.clone());
+        while let Some(value) = todo.pop() {
+            if let Value::Pointer(Address::HeapAddress(i)) = value {
+                if reachable.insert(i) {
+                    if let Ok(v) = self.heap.read(i) {
+                        todo.push(v);
+                    }
+                }
+            }
+        }
+        let leaks = self.heap.collect_leaks(&reachable);
+        self.leaks.extend(leaks);
+    }
+
+    // les NameSpace de la pile, du plus ancien au plus récent
+    pub fn namespaces(&self) -> impl Iterator<Item = (usize, &NameSpace)> {
+        self.stack.iter().enumerate()
+    }
+
+    pub fn heap_cells(&self) -> impl Iterator<Item = (usize, &MemoryCell, &Expression)> {
+        self.heap.cells()
+    }
+
+    pub fn take_leaks(&mut self) -> Vec<EvalError> {
+        std::mem::take(&mut self.leaks)
     }
 
     pub fn declare(&mut self, id: &Identifier, mutable: bool, value: Value) -> Result<(), EvalError> {
@@ -23,16 +135,40 @@ impl NameSpaceStack {
     }
 
     pub fn find(&self, id: &Identifier) -> Result<Value, EvalError> {
-        for ns in self.stack.iter().rev() {
-            if let Ok(v) = ns.find(id) {
-                return Ok(v);
+        for ns in self.stack[self.frame_base()..].iter().rev() {
+            if ns.contains_key(id) {
+                return ns.find(id);
             }
         }
         Err(EvalError::Undefined(id.clone()))
     }
 
+    // variables visibles depuis l'appel en cours, NameSpace par NameSpace,
+    // dans l'ordre de leurs indices
+    pub fn scopes(&self) -> Vec<Vec<Identifier>> {
+        self.stack[self.frame_base()..].iter().map(|ns| ns.identifiers().cloned().collect()).collect()
+    }
+
+    // variable d'indice `slot` du NameSpace `depth`, compté depuis la base
+    // de l'appel en cours
+    pub fn find_slot(&self, depth: usize, slot: usize) -> Result<Value, EvalError> {
+        self.stack[self.frame_base() + depth].find_slot(slot)
+    }
+
+    pub fn set_slot(&mut self, depth: usize, slot: usize, value: Value) -> Result<(), EvalError> {
+        let base = self.frame_base();
+        self.stack[base + depth].set_slot(slot, value)
+    }
+
+    pub fn move_slot(&mut self, depth: usize, slot: usize) -> Result<(), EvalError> {
+        let base = self.frame_base();
+        self.stack[base + depth].move_slot(slot)
+    }
+
+    #[allow(dead_code)]
     pub fn set(&mut self, id: &Identifier, value: Value) -> Result<(), EvalError> {
-        for ns in self.stack.iter_mut().rev() {
+        let base = self.frame_base();
+        for ns in self.stack[base..].iter_mut().rev() {
             let res = ns.set(id, value.clone());
             match res {
                 Ok(_) => return Ok(()),
@@ -44,6 +180,62 @@ impl NameSpaceStack {
         }
         Err(EvalError::Undefined(id.clone()))
     }
+
+    pub fn get_address(&self, id: &Identifier) -> Result<Address, EvalError> {
+        let base = self.frame_base();
+        for (ns, ns_id) in self.stack[base..].iter().zip(&self.ids[base..]).rev() {
+            if ns.contains_key(id) {
+                return Ok(Address::StackAddress(*ns_id, id.clone()));
+            }
+        }
+        Err(EvalError::Undefined(id.clone()))
+    }
+
+    // NameSpace encore empilé qui contient la variable `id`
+    fn namespace(&self, ns_id: usize, id: &Identifier) -> Result<usize, EvalError> {
+        match self.ids.iter().position(|i| *i == ns_id) {
+            Some(depth) if self.stack[depth].contains_key(id) => Ok(depth),
+            _ => Err(EvalError::NonAllocatedCell(None)),
+        }
+    }
+
+    pub fn malloc(&mut self, origin: Expression) -> Address {
+        self.heap.malloc(origin)
+    }
+
+    pub fn read_at(&self, address: &Address) -> Result<Value, EvalError> {
+        match address {
+            Address::StackAddress(ns_id, id) => self.stack[self.namespace(*ns_id, id)?].find(id),
+            Address::HeapAddress(i) => self.heap.read(*i),
+        }
+    }
+
+    pub fn write_at(&mut self, address: &Address, value: Value) -> Result<(), EvalError> {
+        match address {
+            Address::StackAddress(ns_id, id) => {
+                let depth = self.namespace(*ns_id, id)?;
+                self.stack[depth].set(id, value)
+            },
+            Address::HeapAddress(i) => self.heap.write(*i, value),
+        }
+    }
+
+    pub fn move_out(&mut self, address: &Address) -> Result<(), EvalError> {
+        match address {
+            Address::StackAddress(ns_id, id) => {
+                let depth = self.namespace(*ns_id, id)?;
+                self.stack[depth].move_out(id)
+            },
+            Address::HeapAddress(i) => self.heap.move_out(*i),
+        }
+    }
+
+    pub fn free(&mut self, address: &Address) -> Result<(), EvalError> {
+        match address {
+            Address::StackAddress(_, _) => Err(EvalError::NonAllocatedCell(None)),
+            Address::HeapAddress(i) => self.heap.free(*i),
+        }
+    }
 }
 
 /* 
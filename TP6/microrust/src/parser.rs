@@ -3,7 +3,7 @@ use std::fmt::{self, Display};
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub enum ParseError {
-    CannotParse,
+    CannotParse{line: usize, column: usize, fragment: String, expected: Vec<String>},
     SyntaxNotSupported,
 }
 
@@ -11,7 +11,14 @@ impl Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use ParseError::*;
         match self {
-            CannotParse => write!(f, "Cannot parse"),
+            CannotParse{line, column, fragment, expected} => {
+                write!(f, "Cannot parse {} at line {}, column {}", 
+                    if fragment.is_empty() { "end of input".to_string() } else { format!("`{}`", fragment) }, line, column)?;
+                if !expected.is_empty() {
+                    write!(f, ", expected one of: {}", expected.join(", "))?;
+                }
+                Ok(())
+            },
             SyntaxNotSupported => write!(f, "Syntax not supported"),
         }
     }
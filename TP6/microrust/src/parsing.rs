@@ -7,6 +7,8 @@ pub mod expression;
 pub mod instruction;
 pub mod utils;
 pub mod parsedvalue;
+pub mod function;
+pub mod span;
 
 
 
@@ -48,7 +48,7 @@ use crate::parser::{Parse, ParseError};
 impl Parse for Binop {
 
     fn parse(input: &str) -> Result<Self, ParseError> {
-        match PestParser::parse(Rule::start_rule_binop, &input) {
+        match PestParser::parse(Rule::start_rule_binop, input) {
             Ok(mut pairs) => {
                 let first_rule = pairs.next().unwrap();
                 match first_rule.as_rule() {
@@ -58,7 +58,7 @@ impl Parse for Binop {
                     _ => { panic!("the grammar is not as expected") }
                 }                
             },
-            Err(_e) => { Err(ParseError::CannotParse) }
+            Err(e) => { Err(ParseError::from(e)) }
         }
     }
 }
\ No newline at end of file
@@ -1,8 +1,15 @@
 use super::binop::Binop;
 use super::parsedvalue::ParsedValue;
+use super::span::Span;
 
 #[derive(Debug, Clone)]
-pub enum Expression {
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpressionKind {
     Const(ParsedValue),
     Identifier(Identifier),
     BinOp(Box<Expression>, Binop, Box<Expression>),
@@ -14,6 +21,48 @@ pub enum Expression {
     NewPtr,
     Deref(Box<Expression>),
     AmpersAnd(Box<Expression>),
+    Call(Identifier, Vec<Expression>),
+}
+
+
+impl Expression {
+    pub fn new(kind: ExpressionKind, span: Span) -> Self {
+        Expression { kind, span }
+    }
+
+    // décale la position de l'expression et de ses sous-expressions
+    pub fn shift_spans(&mut self, offset: usize) {
+        use ExpressionKind::*;
+        self.span = self.span.shift(offset);
+        match &mut self.kind {
+            Const(_) | Identifier(_) | NewPtr => (),
+            BinOp(e1, _, e2) => {
+                e1.shift_spans(offset);
+                e2.shift_spans(offset);
+            },
+            Conditional{cond, cond_true, cond_false} => {
+                cond.shift_spans(offset);
+                cond_true.shift_spans(offset);
+                cond_false.shift_spans(offset);
+            },
+            Deref(e) | AmpersAnd(e) => e.shift_spans(offset),
+            Call(_, args) => args.iter_mut().for_each(|e| e.shift_spans(offset)),
+        }
+    }
+}
+
+// deux expressions sont égales si elles ont le même arbre, où qu'elles soient écrites
+impl PartialEq for Expression {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+// expression construite hors du parseur, sans position dans le source
+impl From<ExpressionKind> for Expression {
+    fn from(kind: ExpressionKind) -> Self {
+        Expression::new(kind, Span::default())
+    }
 }
 
 
@@ -21,7 +70,13 @@ use std::fmt::Display;
 
 impl Display for Expression {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        use Expression::*;
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl Display for ExpressionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use ExpressionKind::*;
         match self {
             Const(i) => write!(f, "{}", i),
             BinOp(lhs, op, rhs) => write!(f, "({} {} {})", lhs, op, rhs),
@@ -31,6 +86,7 @@ impl Display for Expression {
             NewPtr => write!(f,  "Ptr::new()"),
             Deref(e) => write!(f, "*{}", e),
             AmpersAnd(e) => write!(f, "&{}", e),
+            Call(id, args) => write!(f, "{}({})", id, args.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(", ")),
         }
     }
 }
@@ -45,7 +101,7 @@ use crate::{identifier::Identifier, parser::{ Parse, ParseError}};
 impl Parse for Expression {
 
     fn parse(input: &str) -> Result<Self, ParseError> {
-        match PestParser::parse(Rule::start_rule_expr, &input) {
+        match PestParser::parse(Rule::start_rule_expr, input) {
             Ok(mut pairs) => {
                 let first_rule = pairs.next().unwrap();
                 match first_rule.as_rule() {
@@ -55,7 +111,7 @@ impl Parse for Expression {
                     _ => { panic!("the grammar is not as expected") }
                 }                
             },
-            Err(_e) => { Err(ParseError::CannotParse) }
+            Err(e) => { Err(ParseError::from(e)) }
         }
     }
 }
\ No newline at end of file
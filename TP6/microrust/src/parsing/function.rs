@@ -0,0 +1,38 @@
+use crate::identifier::Identifier;
+use crate::parsing::instruction::Instruction;
+use crate::r#type::Type;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub id: Identifier,
+    pub mutable: bool,
+    pub ty: Type,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub id: Identifier,
+    pub params: Vec<Param>,
+    pub ret: Type,
+    pub body: Instruction,
+}
+
+
+use std::fmt::Display;
+
+impl Display for Param {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.mutable {
+            write!(f, "mut {}: {}", self.id, self.ty)
+        } else {
+            write!(f, "{}: {}", self.id, self.ty)
+        }
+    }
+}
+
+impl Display for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let params = self.params.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(", ");
+        write!(f, "fn {}({}) -> {} {}", self.id, params, self.ret, self.body)
+    }
+}
@@ -1,8 +1,17 @@
 use crate::parsing::expression::Expression;
 use crate::identifier::Identifier;
+use crate::parsing::function::Function;
+use crate::parsing::span::Span;
+use std::rc::Rc;
 
 #[derive(Debug, Clone)]
-pub enum Instruction {
+pub struct Instruction {
+    pub kind: InstructionKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstructionKind {
     Expr(Expression),
     Let{id:Identifier, mutable:bool, expr:Expression},
     Block(Vec<Instruction>),
@@ -14,12 +23,61 @@ pub enum Instruction {
     While(Expression, Box<Instruction>),
     WriteAt(Expression, Expression),
     Free(Expression),
+    FnDef(Rc<Function>),
+    Return(Expression),
 } 
 
+impl Instruction {
+    pub fn new(kind: InstructionKind, span: Span) -> Self {
+        Instruction { kind, span }
+    }
+
+    // décale la position de l'instruction et de ses sous-nœuds, pour
+    // qu'elle désigne l'entrée dans l'ensemble du texte source
+    pub fn shift_spans(&mut self, offset: usize) {
+        use InstructionKind::*;
+        self.span = self.span.shift(offset);
+        match &mut self.kind {
+            Expr(e) | Let{expr: e, ..} | Free(e) | Return(e) => e.shift_spans(offset),
+            Block(instrs) => instrs.iter_mut().for_each(|i| i.shift_spans(offset)),
+            IfElse{cond, cond_true, cond_false} => {
+                cond.shift_spans(offset);
+                cond_true.shift_spans(offset);
+                cond_false.shift_spans(offset);
+            },
+            While(cond, body) => {
+                cond.shift_spans(offset);
+                body.shift_spans(offset);
+            },
+            WriteAt(e1, e2) => {
+                e1.shift_spans(offset);
+                e2.shift_spans(offset);
+            },
+            // la fonction vient d'être construite par le parseur : elle n'est pas partagée
+            FnDef(function) => if let Some(function) = Rc::get_mut(function) {
+                function.body.shift_spans(offset);
+            },
+        }
+    }
+}
+
+// comme pour les expressions, la position dans le source est ignorée
+impl PartialEq for Instruction {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
 use std::fmt::Display;
 impl Display for Instruction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        use Instruction::*;
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl Display for InstructionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use InstructionKind::*;
         match self {
             Expr(expr) => write!(f, "{}", expr),
             Let{id, mutable, expr} => {
@@ -30,7 +88,7 @@ impl Display for Instruction {
                 }
             },
             Block(instrs) => {
-                write!(f, "{{{}}}", instrs.into_iter().map(|x| x.to_string()).collect::<Vec<_>>().join(";"))
+                write!(f, "{{{}}}", instrs.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(";"))
             },
             IfElse{cond, cond_true, cond_false} => {
                 write!(f, "if {} {} else {}", cond, cond_true, cond_false)
@@ -44,6 +102,8 @@ impl Display for Instruction {
             Free(lexpr) => {
                 write!(f, "free {}", lexpr)
             },
+            FnDef(function) => write!(f, "{}", function),
+            Return(expr) => write!(f, "return {}", expr),
         }
     }
 }
@@ -51,11 +111,11 @@ impl Display for Instruction {
 
 use pest::Parser;
 use crate::parser::{ParseError, Parse};
-use super::utils::{PestParser, Rule, parse_instr};
+use super::utils::{PestParser, Rule, parse_instr, parse_program};
 
 impl Parse for Instruction {
     fn parse(input: &str) -> Result<Self, ParseError> {
-        match PestParser::parse(Rule::start_rule_instr, &input) {
+        match PestParser::parse(Rule::start_rule_instr, input) {
             Ok(mut pairs) => {
                 let first_rule = pairs.next().unwrap();
                 match first_rule.as_rule() {
@@ -65,8 +125,17 @@ impl Parse for Instruction {
                     _ => { panic!("the grammar is not as expected") }
                 }                
             },
-            Err(_e) => { Err(ParseError::CannotParse) }
+            Err(e) => { Err(ParseError::from(e)) }
         }
     }
 
 }
+
+impl Parse for Vec<Instruction> {
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        match PestParser::parse(Rule::start_rule_program, input) {
+            Ok(pairs) => parse_program(pairs),
+            Err(e) => { Err(ParseError::from(e)) }
+        }
+    }
+}
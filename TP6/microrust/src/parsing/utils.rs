@@ -7,13 +7,17 @@ use pest::pratt_parser::PrattParser;
 #[grammar = "parsing/grammar.pest"]
 pub struct PestParser;
 
-use super::instruction::Instruction;
-use super::expression::Expression;
+use super::instruction::{Instruction, InstructionKind};
+use super::expression::{Expression, ExpressionKind};
+use super::span::Span;
 use super::binop::Binop;
 use super::parsedvalue::ParsedValue;
+use super::function::{Function, Param};
 
 use crate::identifier::Identifier;
 use crate::parser::ParseError;
+use crate::r#type::Type;
+use std::rc::Rc;
 
 lazy_static::lazy_static! {
     static ref PRATT_PARSER: PrattParser<Rule> = {
@@ -58,50 +62,112 @@ pub fn parse_binop_rule(first_rule: Pair<'_, Rule>) -> Binop {
 
 pub fn parse_expr(pairs: Pairs<Rule>) -> Expression {
     PRATT_PARSER
-        .map_primary(|primary| match primary.as_rule() {
-            Rule::integer => Expression::Const(ParsedValue::Integer(primary.as_str().parse::<isize>().unwrap())),
-            Rule::boolean => Expression::Const(ParsedValue::Boolean(match primary.as_str() {
-                "true" => true,
-                "false" => false,
-                _ => unreachable!()
-            })),
-            Rule::expr => parse_expr(primary.into_inner()),
-            Rule::atom => parse_expr(primary.into_inner()),
-            Rule::conditional_expr => {
-                let mut rules = primary.into_inner();
-                let cond = Box::new(parse_expr(rules.next().unwrap().into_inner()));
-                let cond_true = Box::new(parse_expr(rules.next().unwrap().into_inner()));
-                let cond_false = Box::new(parse_expr(rules.next().unwrap().into_inner()));
-                Expression::Conditional{cond, cond_true, cond_false}
-            },
-            Rule::unit => Expression::Const(ParsedValue::Unit),
-            Rule::identifier => Expression::Identifier(Identifier::from(primary.as_str())),
-            Rule::ptrnew => Expression::NewPtr,
-            Rule::deref => {
-                let expr = Box::new(parse_expr(primary.into_inner()));
-                Expression::Deref(expr)
-            },
-            Rule::ampersand => {
-                let expr = Box::new(parse_expr(primary.into_inner()));
-                Expression::AmpersAnd(expr)
-            },
-            rule => unreachable!("parse_expr expected atom, found {:?}", rule),
+        .map_primary(|primary| {
+            let span = Span::from(primary.as_span());
+            let kind = match primary.as_rule() {
+                Rule::integer => ExpressionKind::Const(ParsedValue::Integer(primary.as_str().parse::<isize>().unwrap())),
+                Rule::boolean => ExpressionKind::Const(ParsedValue::Boolean(match primary.as_str() {
+                    "true" => true,
+                    "false" => false,
+                    _ => unreachable!()
+                })),
+                Rule::expr => return parse_expr(primary.into_inner()),
+                Rule::atom => return parse_expr(primary.into_inner()),
+                Rule::conditional_expr => {
+                    let mut rules = primary.into_inner();
+                    let cond = Box::new(parse_expr(rules.next().unwrap().into_inner()));
+                    let cond_true = Box::new(parse_expr(rules.next().unwrap().into_inner()));
+                    let cond_false = Box::new(parse_expr(rules.next().unwrap().into_inner()));
+                    ExpressionKind::Conditional{cond, cond_true, cond_false}
+                },
+                Rule::unit => ExpressionKind::Const(ParsedValue::Unit),
+                Rule::identifier => ExpressionKind::Identifier(Identifier::from(primary.as_str())),
+                Rule::ptrnew => ExpressionKind::NewPtr,
+                Rule::deref => {
+                    let expr = Box::new(parse_expr(primary.into_inner()));
+                    ExpressionKind::Deref(expr)
+                },
+                Rule::ampersand => {
+                    let expr = Box::new(parse_expr(primary.into_inner()));
+                    ExpressionKind::AmpersAnd(expr)
+                },
+                Rule::call => {
+                    let mut rules = primary.into_inner();
+                    let id = Identifier::from(rules.next().unwrap().as_str());
+                    let args = rules.map(|rule| parse_expr(rule.into_inner())).collect();
+                    ExpressionKind::Call(id, args)
+                },
+                rule => unreachable!("parse_expr expected atom, found {:?}", rule),
+            };
+            Expression::new(kind, span)
         })
         .map_infix(|lhs, op, rhs| {
             let op2 = parse_binop_rule(op);
-            Expression::BinOp(Box::new(lhs), op2, Box::new(rhs))
+            let span = lhs.span.merge(&rhs.span);
+            Expression::new(ExpressionKind::BinOp(Box::new(lhs), op2, Box::new(rhs)), span)
         })
         .parse(pairs)
 }
 
-fn parse_block(pairs: &mut Pairs<Rule>) -> Result<Vec<Instruction>, ParseError> {
-    let first_rule = pairs.next().unwrap();
+pub fn parse_type(pair: Pair<Rule>) -> Type {
+    let first_rule = pair.into_inner().next().unwrap();
+    match first_rule.as_rule() {
+        Rule::int_type => Type::Int,
+        Rule::bool_type => Type::Bool,
+        Rule::unit => Type::Unit,
+        Rule::ptr_type => Type::Pointer(Box::new(parse_type(first_rule.into_inner().nth(1).unwrap()))),
+        Rule::ref_type => Type::Ref(Box::new(parse_type(first_rule.into_inner().next().unwrap()))),
+        rule => unreachable!("parse_type expected type, found {:?}", rule),
+    }
+}
+
+fn parse_function(pairs: Pairs<Rule>) -> Result<Function, ParseError> {
+    let mut id = None;
+    let mut params = vec![];
+    let mut ret = Type::Unit;
+    let mut body = None;
+    for rule in pairs {
+        match rule.as_rule() {
+            Rule::identifier => id = Some(Identifier::from(rule.as_str())),
+            Rule::param | Rule::mut_param => {
+                let mutable = rule.as_rule() == Rule::mut_param;
+                let mut rules = rule.into_inner();
+                let id = Identifier::from(rules.next().unwrap().as_str());
+                params.push(Param{id, mutable, ty: parse_type(rules.next().unwrap())});
+            },
+            Rule::type_expr => ret = parse_type(rule),
+            Rule::instrs => body = Some(parse_block(rule)?),
+            rule => unreachable!("parse_function found {:?}", rule),
+        }
+    }
+    Ok(Function{id: id.unwrap(), params, ret, body: body.unwrap()})
+}
+
+// position, fragment de l'entrée et règles attendues à l'endroit de l'échec
+impl From<pest::error::Error<Rule>> for ParseError {
+    fn from(e: pest::error::Error<Rule>) -> Self {
+        use pest::error::{ErrorVariant, LineColLocation};
+        let (line, column) = match e.line_col {
+            LineColLocation::Pos(pos) | LineColLocation::Span(pos, _) => pos,
+        };
+        let fragment = e.line().chars().skip(column - 1).take_while(|c| !c.is_whitespace()).collect();
+        let expected = match &e.variant {
+            ErrorVariant::ParsingError{positives, ..} => positives.iter().map(|rule| format!("{:?}", rule)).collect(),
+            ErrorVariant::CustomError{..} => vec![],
+        };
+        ParseError::CannotParse{line, column, fragment, expected}
+    }
+}
+
+fn parse_block(pair: Pair<Rule>) -> Result<Instruction, ParseError> {
+    let span = Span::from(pair.as_span());
+    let first_rule = pair.into_inner().next().unwrap();
     let mut res = vec![];
     match first_rule.as_rule() {
         Rule::empty_block => { },
         Rule::non_empty_block => {
-            let mut rules = first_rule.into_inner();
-            while let Some(rule) = rules.next(){
+            let rules = first_rule.into_inner();
+            for rule in rules {
                 if rule.as_rule() == Rule::instr {
                     let instr = parse_instr(&mut rule.into_inner())?;
                     res.push(instr)
@@ -110,54 +176,74 @@ fn parse_block(pairs: &mut Pairs<Rule>) -> Result<Vec<Instruction>, ParseError>
         },
         _ => unreachable!("parse_block expected instrs, found {:?}", first_rule),
     };
+    Ok(Instruction::new(InstructionKind::Block(res), span))
+}
+
+// un programme : une suite d'instructions, éventuellement séparées par `;`
+pub fn parse_program(pairs: Pairs<Rule>) -> Result<Vec<Instruction>, ParseError> {
+    let mut res = vec![];
+    for rule in pairs {
+        if rule.as_rule() == Rule::instr {
+            res.push(parse_instr(&mut rule.into_inner())?);
+        }
+    }
     Ok(res)
 }
 
 pub fn parse_instr(pairs: &mut Pairs<Rule>) -> Result<Instruction, ParseError> {
 //    println!("{}\n{:?}\n\n", pairs.as_str(), pairs);
     let first_rule = pairs.next().unwrap();
-    match first_rule.as_rule() {
-        Rule::expr => Ok(Instruction::Expr(parse_expr(first_rule.into_inner()))),
+    let span = Span::from(first_rule.as_span());
+    let kind = match first_rule.as_rule() {
+        Rule::expr => InstructionKind::Expr(parse_expr(first_rule.into_inner())),
         Rule::let_equals => {
             let mut rules = first_rule.into_inner();
             let id = rules.next().unwrap().as_span().as_str().to_string();
             let id = Identifier::from(id.as_str());
             let expr = rules.next().unwrap().into_inner();
-            Ok(Instruction::Let{id, mutable: false, expr: parse_expr(expr) })
+            InstructionKind::Let{id, mutable: false, expr: parse_expr(expr) }
         },
         Rule::let_mut_equals => {
             let mut rules = first_rule.into_inner();
             let id = rules.next().unwrap().as_span().as_str().to_string();
             let id = Identifier::from(id.as_str());
             let expr = rules.next().unwrap().into_inner();
-            Ok(Instruction::Let{id, mutable: true, expr: parse_expr(expr) })
+            InstructionKind::Let{id, mutable: true, expr: parse_expr(expr) }
         },
         Rule::write_at => {
             let mut rules = first_rule.into_inner();
             let lexpr = parse_expr(rules.next().unwrap().into_inner());
             let expr = parse_expr(rules.next().unwrap().into_inner());
-            Ok(Instruction::WriteAt(lexpr, expr))
+            InstructionKind::WriteAt(lexpr, expr)
         },
         Rule::if_instr => {
             let mut rules = first_rule.into_inner();
             let cond = parse_expr(rules.next().unwrap().into_inner());
-            let cond_true = Box::new(Instruction::Block(parse_block(&mut rules.next().unwrap().into_inner())?));
-            let cond_false = Box::new(Instruction::Block(parse_block(&mut rules.next().unwrap().into_inner())?));
-            Ok(Instruction::IfElse{cond, cond_true, cond_false})
+            let cond_true = Box::new(parse_block(rules.next().unwrap())?);
+            let cond_false = Box::new(parse_block(rules.next().unwrap())?);
+            InstructionKind::IfElse{cond, cond_true, cond_false}
         },
         Rule::while_instr => {
             let mut rules = first_rule.into_inner();
             let cond = parse_expr(rules.next().unwrap().into_inner());
-            let instr = Instruction::Block(parse_block(&mut rules.next().unwrap().into_inner())?);
-            Ok(Instruction::While(cond, Box::new(instr)))
-        },
-        Rule::instrs => {
-            Ok(Instruction::Block(parse_block(&mut first_rule.into_inner())?))
+            let instr = parse_block(rules.next().unwrap())?;
+            InstructionKind::While(cond, Box::new(instr))
         },
+        Rule::instrs => return parse_block(first_rule),
         Rule::free_instr => {
             let lexpr = parse_expr(first_rule.into_inner());
-            Ok(Instruction::Free(lexpr))
+            InstructionKind::Free(lexpr)
+        },
+        Rule::fn_def => {
+            InstructionKind::FnDef(Rc::new(parse_function(first_rule.into_inner())?))
+        },
+        Rule::return_instr => {
+            match first_rule.into_inner().next() {
+                Some(rule) => InstructionKind::Return(parse_expr(rule.into_inner())),
+                None => InstructionKind::Return(Expression::new(ExpressionKind::Const(ParsedValue::Unit), span)),
+            }
         },
         _ => unreachable!("parse_instr expected instr, found {:?}", first_rule),
-    }
-}
\ No newline at end of file
+    };
+    Ok(Instruction::new(kind, span))
+}
@@ -0,0 +1,220 @@
+use crate::parsing::binop::Binop;
+use crate::parsing::expression::{Expression, ExpressionKind};
+use crate::parsing::instruction::{Instruction, InstructionKind};
+use crate::parsing::parsedvalue::ParsedValue;
+use crate::r#type::Type;
+use ExpressionKind::*;
+
+const INDENT: &str = "    ";
+
+// commentaires du texte source, qui ne font pas partie de l'AST : chacun est
+// réécrit sur sa propre ligne, avant l'instruction qui le suit dans son bloc
+struct Comments {
+    comments: Vec<(usize, String)>,
+    next: usize,
+}
+
+impl Comments {
+    // mêmes règles que `COMMENT` dans la grammaire : `//` jusqu'à la fin de la
+    // ligne, `/* */` imbriqués
+    fn new(source: &str) -> Self {
+        let bytes = source.as_bytes();
+        let mut comments = vec![];
+        let mut i = 0;
+        while i + 1 < bytes.len() {
+            let start = i;
+            match &bytes[i..i + 2] {
+                b"//" => i = source[i..].find('\n').map_or(source.len(), |n| i + n),
+                b"/*" => {
+                    let mut depth = 0;
+                    while i < bytes.len() {
+                        if bytes[i..].starts_with(b"/*") {
+                            depth += 1;
+                            i += 2;
+                        } else if bytes[i..].starts_with(b"*/") {
+                            depth -= 1;
+                            i += 2;
+                            if depth == 0 {
+                                break;
+                            }
+                        } else {
+                            i += 1;
+                        }
+                    }
+                },
+                _ => {
+                    i += 1;
+                    continue;
+                },
+            }
+            comments.push((start, source[start..i].trim_end().to_string()));
+        }
+        Comments { comments, next: 0 }
+    }
+
+    // les commentaires pas encore écrits qui commencent avant `position`
+    fn lines_before(&mut self, position: usize, indent: &str) -> String {
+        let mut res = String::new();
+        while let Some((start, comment)) = self.comments.get(self.next) {
+            if *start >= position {
+                break;
+            }
+            res.push_str(&format!("{}{}\n", indent, comment));
+            self.next += 1;
+        }
+        res
+    }
+}
+
+// niveau de priorité des opérateurs, dans l'ordre du PRATT_PARSER
+fn precedence(op: &Binop) -> usize {
+    use Binop::*;
+    match op {
+        And | Or => 1,
+        Eq | Neq | Leq | Geq | Lt | Gt => 2,
+        Add | Sub => 3,
+        Mul | Div | Mod => 4,
+    }
+}
+
+// écriture d'un type telle que l'accepte la règle `type_expr`
+fn pretty_type(t: &Type) -> String {
+    match t {
+        Type::Int => "isize".to_string(),
+        Type::Bool => "bool".to_string(),
+        Type::Unit => "()".to_string(),
+        Type::Pointer(t) => format!("Ptr<{}>", pretty_type(t)),
+        Type::Ref(t) => format!("&{}", pretty_type(t)),
+        Type::Var(_) | Type::Unknown => "_".to_string(),
+    }
+}
+
+
+impl Expression {
+    // la branche `else` d'une conditionnelle s'étend aussi loin que possible :
+    // elle est parenthésée dès qu'elle est l'opérande d'un opérateur
+    fn pretty_operand(&self, parenthesize: bool) -> String {
+        let parenthesize = parenthesize || matches!(self.kind, Conditional{..});
+        if parenthesize {
+            format!("({})", self.pretty())
+        } else {
+            self.pretty()
+        }
+    }
+
+    // les opérateurs sont associatifs à gauche : seul l'opérande droit est
+    // parenthésé quand il a la même priorité que l'opérateur
+    pub fn pretty(&self) -> String {
+        match &self.kind {
+            Const(value) => value.to_string(),
+            Identifier(id) => id.to_string(),
+            BinOp(e1, op, e2) => {
+                let lhs = match &e1.kind {
+                    BinOp(_, op1, _) => e1.pretty_operand(precedence(op1) < precedence(op)),
+                    _ => e1.pretty_operand(false),
+                };
+                let rhs = match &e2.kind {
+                    BinOp(_, op2, _) => e2.pretty_operand(precedence(op2) <= precedence(op)),
+                    _ => e2.pretty_operand(false),
+                };
+                format!("{} {} {}", lhs, op, rhs)
+            },
+            Conditional{cond, cond_true, cond_false} =>
+                format!("({}) ? {} : {}", cond.pretty(), cond_true.pretty(), cond_false.pretty()),
+            NewPtr => "Ptr::new()".to_string(),
+            // `*` et `&` ne s'appliquent qu'à un atome
+            Deref(e) => format!("*{}", e.pretty_operand(matches!(e.kind, BinOp(..)))),
+            AmpersAnd(e) => format!("&{}", e.pretty_operand(matches!(e.kind, BinOp(..)))),
+            Call(id, args) => format!("{}({})", id, args.iter().map(|e| e.pretty()).collect::<Vec<_>>().join(", ")),
+        }
+    }
+}
+
+
+impl Instruction {
+    // un commentaire à l'intérieur d'une instruction sans bloc est écrit avant
+    // elle ; dans les autres, il rejoint le bloc qui le suit
+    fn comments_end(&self) -> usize {
+        match self.kind {
+            InstructionKind::Block(_) | InstructionKind::IfElse{..}
+            | InstructionKind::While(..) | InstructionKind::FnDef(_) => self.span.start,
+            _ => self.span.end,
+        }
+    }
+
+    // les instructions d'un bloc sont séparées par `;`, la dernière n'en a pas
+    fn pretty(&self, depth: usize, comments: &mut Comments) -> String {
+        match &self.kind {
+            InstructionKind::Expr(e) => e.pretty(),
+            InstructionKind::Let{id, mutable, expr} => {
+                let mutable = if *mutable { "mut " } else { "" };
+                format!("let {}{} = {}", mutable, id, expr.pretty())
+            },
+            InstructionKind::Block(instrs) => {
+                let indent = INDENT.repeat(depth + 1);
+                let mut body = String::new();
+                for (i, instr) in instrs.iter().enumerate() {
+                    body.push_str(&comments.lines_before(instr.comments_end(), &indent));
+                    body.push_str(&format!("{}{}", indent, instr.pretty(depth + 1, comments)));
+                    if i + 1 < instrs.len() {
+                        body.push(';');
+                    }
+                    body.push('\n');
+                }
+                body.push_str(&comments.lines_before(self.span.end, &indent));
+                if body.is_empty() {
+                    "{}".to_string()
+                } else {
+                    format!("{{\n{}{}}}", body, INDENT.repeat(depth))
+                }
+            },
+            InstructionKind::IfElse{cond, cond_true, cond_false} =>
+                format!("if {} {} else {}", cond.pretty(), cond_true.pretty(depth, comments), cond_false.pretty(depth, comments)),
+            InstructionKind::While(cond, body) => format!("while {} {}", cond.pretty(), body.pretty(depth, comments)),
+            InstructionKind::WriteAt(e1, e2) => format!("{} = {}", e1.pretty(), e2.pretty()),
+            InstructionKind::Free(e) => format!("free({})", e.pretty()),
+            InstructionKind::FnDef(function) => {
+                let params = function.params.iter()
+                    .map(|param| {
+                        let mutable = if param.mutable { "mut " } else { "" };
+                        format!("{}{}: {}", mutable, param.id, pretty_type(&param.ty))
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let ret = match function.ret {
+                    Type::Unit => String::new(),
+                    ref t => format!(" -> {}", pretty_type(t)),
+                };
+                format!("fn {}({}){} {}", function.id, params, ret, function.body.pretty(depth, comments))
+            },
+            InstructionKind::Return(e) => match e.kind {
+                Const(ParsedValue::Unit) => "return".to_string(),
+                _ => format!("return {}", e.pretty()),
+            },
+        }
+    }
+}
+
+// un programme : les instructions qui ne finissent pas par un bloc prennent
+// un `;`, et les définitions de fonctions sont séparées par une ligne vide ;
+// `source` est le texte d'où viennent `instrs`, pour en garder les commentaires
+pub fn pretty_program(instrs: &[Instruction], source: &str) -> String {
+    let mut comments = Comments::new(source);
+    let mut res = String::new();
+    for (i, instr) in instrs.iter().enumerate() {
+        let is_fn = matches!(instr.kind, InstructionKind::FnDef(_));
+        if i > 0 && (is_fn || matches!(instrs[i - 1].kind, InstructionKind::FnDef(_))) {
+            res.push('\n');
+        }
+        res.push_str(&comments.lines_before(instr.comments_end(), ""));
+        res.push_str(&instr.pretty(0, &mut comments));
+        match instr.kind {
+            InstructionKind::Block(_) | InstructionKind::IfElse{..}
+            | InstructionKind::While(..) | InstructionKind::FnDef(_) => (),
+            _ => res.push(';'),
+        }
+        res.push('\n');
+    }
+    res.push_str(&comments.lines_before(usize::MAX, ""));
+    res
+}
@@ -0,0 +1,91 @@
+use crate::{
+    error::EvalError,
+    namespace::NameSpace,
+    namespacestack::NameSpaceStack,
+    parser::Parse,
+    parsing::instruction::Instruction,
+    value::Value,
+};
+
+fn exec(nss: &mut NameSpaceStack, input: &str) -> Result<Value, EvalError> {
+    Instruction::parse(input).unwrap().exec(nss).map(|(_, value)| value).map_err(EvalError::without_location)
+}
+
+fn new_stack() -> NameSpaceStack {
+    let mut nss = NameSpaceStack::new();
+    nss.push(NameSpace::new());
+    nss
+}
+
+#[test]
+fn test_recursion() {
+    let mut nss = new_stack();
+    assert!(exec(&mut nss, "fn fact(n: isize) -> isize { if n <= 1 { return 1 } else { n * fact(n - 1) } }").is_ok());
+    assert_eq!(exec(&mut nss, "fact(6)").unwrap(), Value::Integer(720));
+}
+
+#[test]
+fn test_lexical_scoping() {
+    let mut nss = new_stack();
+    assert!(exec(&mut nss, "let x = 1").is_ok());
+    assert!(exec(&mut nss, "fn f() -> isize { x }").is_ok());
+    match exec(&mut nss, "f()") {
+        Err(EvalError::Undefined(_)) => (),
+        _ => panic!("The callee should not see the caller's locals"),
+    }
+    assert_eq!(exec(&mut nss, "x").unwrap(), Value::Integer(1));
+}
+
+#[test]
+fn test_arguments() {
+    let mut nss = new_stack();
+    assert!(exec(&mut nss, "fn incr(r: &isize, n: isize) { *r = *r + n }").is_ok());
+    assert!(exec(&mut nss, "let mut y = 1").is_ok());
+    assert_eq!(exec(&mut nss, "incr(&y, 2)").unwrap(), Value::Unit);
+    assert_eq!(exec(&mut nss, "y").unwrap(), Value::Integer(3));
+    match exec(&mut nss, "incr(&y)") {
+        Err(EvalError::WrongArity{expected: 2, found: 1, ..}) => (),
+        _ => panic!("Expected an arity error"),
+    }
+}
+
+#[test]
+fn test_free_call_result() {
+    let mut nss = new_stack();
+    assert!(exec(&mut nss, "fn h() -> Ptr<isize> { Ptr::new() }").is_ok());
+    assert_eq!(exec(&mut nss, "free(h())").unwrap(), Value::Unit);
+    assert!(nss.take_leaks().is_empty());
+}
+
+#[test]
+fn test_use_after_free() {
+    let mut nss = new_stack();
+    assert!(exec(&mut nss, "let p = Ptr::new()").is_ok());
+    assert_eq!(exec(&mut nss, "free(p)").unwrap(), Value::Unit);
+    assert!(matches!(exec(&mut nss, "*p"), Err(EvalError::UseAfterFree(_))));
+    assert!(matches!(exec(&mut nss, "free(p)"), Err(EvalError::UseAfterFree(_))));
+    assert!(exec(&mut nss, "let q = Ptr::new()").is_ok());
+    assert!(exec(&mut nss, "let r = &q").is_ok());
+    assert_eq!(exec(&mut nss, "free(*r)").unwrap(), Value::Unit);
+    assert!(matches!(exec(&mut nss, "*q"), Err(EvalError::UseAfterFree(_))));
+}
+
+#[test]
+fn test_pending_arguments_are_reachable() {
+    let mut nss = new_stack();
+    assert!(exec(&mut nss, "fn g() -> isize { let q = Ptr::new(); free(q); 1 }").is_ok());
+    assert!(exec(&mut nss, "fn f(p: Ptr<isize>, x: isize) -> isize { free(p); x }").is_ok());
+    assert_eq!(exec(&mut nss, "f(Ptr::new(), g())").unwrap(), Value::Integer(1));
+    assert!(nss.take_leaks().is_empty());
+}
+
+#[test]
+fn test_overflow() {
+    let mut nss = new_stack();
+    assert!(matches!(exec(&mut nss, "9223372036854775807 * 2"), Err(EvalError::Overflow(_))));
+    assert!(exec(&mut nss, "let min = 0 - 9223372036854775807 - 1").is_ok());
+    assert!(matches!(exec(&mut nss, "min / (0 - 1)"), Err(EvalError::Overflow(_))));
+    assert!(matches!(exec(&mut nss, "min % (0 - 1)"), Err(EvalError::Overflow(_))));
+    assert!(matches!(exec(&mut nss, "min - 1"), Err(EvalError::Overflow(_))));
+    assert_eq!(exec(&mut nss, "min + 1 - 1").unwrap(), Value::Integer(isize::MIN));
+}
@@ -0,0 +1,96 @@
+use std::collections::HashSet;
+
+use crate::{
+    error::EvalError,
+    identifier::Identifier,
+    memory::{Address, Memory},
+    namespace::NameSpace,
+    namespacestack::NameSpaceStack,
+    parsing::expression::{Expression, ExpressionKind},
+    value::Value,
+};
+
+#[test]
+fn test_read_write() {
+    let mut mem = Memory::new();
+    let i = match mem.malloc(Expression::from(ExpressionKind::NewPtr)) {
+        Address::HeapAddress(i) => i,
+        _ => panic!("malloc should return a heap address"),
+    };
+    match mem.read(i) {
+        Err(EvalError::NonInitializedValue(None)) => (),
+        _ => panic!("Fresh cell should not be initialized"),
+    }
+    assert!(mem.write(i, Value::Integer(42)).is_ok());
+    assert_eq!(mem.read(i).unwrap(), Value::Integer(42));
+}
+
+#[test]
+fn test_free() {
+    let mut mem = Memory::new();
+    match mem.read(0) {
+        Err(EvalError::NonAllocatedCell(None)) => (),
+        _ => panic!("Cell should not be allocated"),
+    }
+    mem.malloc(Expression::from(ExpressionKind::NewPtr));
+    assert!(mem.free(0).is_ok());
+    match mem.read(0) {
+        Err(EvalError::UseAfterFree(None)) => (),
+        _ => panic!("Expected use after free"),
+    }
+    match mem.free(0) {
+        Err(EvalError::UseAfterFree(None)) => (),
+        _ => panic!("Expected double free to be detected"),
+    }
+}
+
+#[test]
+fn test_collect_leaks() {
+    let mut mem = Memory::new();
+    mem.malloc(Expression::from(ExpressionKind::NewPtr));
+    mem.malloc(Expression::from(ExpressionKind::NewPtr));
+    let leaks = mem.collect_leaks(&HashSet::from([0]));
+    match leaks.as_slice() {
+        [EvalError::MemoryLeak(Address::HeapAddress(1), _)] => (),
+        _ => panic!("Only the second cell should leak"),
+    }
+    match mem.read(1) {
+        Err(EvalError::UseAfterFree(None)) => (),
+        _ => panic!("Leaked cell should be reclaimed"),
+    }
+    assert!(!mem.is_empty());
+    assert!(mem.free(0).is_ok());
+    assert!(mem.is_empty());
+    assert!(mem.collect_leaks(&HashSet::new()).is_empty());
+}
+
+#[test]
+fn test_move_out() {
+    let mut mem = Memory::new();
+    mem.malloc(Expression::from(ExpressionKind::NewPtr));
+    assert!(mem.write(0, Value::Integer(1)).is_ok());
+    assert!(mem.move_out(0).is_ok());
+    match mem.read(0) {
+        Err(EvalError::MovedValue(None)) => (),
+        _ => panic!("Expected moved value"),
+    }
+    assert!(mem.write(0, Value::Integer(2)).is_ok());
+    assert_eq!(mem.read(0).unwrap(), Value::Integer(2));
+}
+
+#[test]
+fn test_stale_stack_address() {
+    let mut nss = NameSpaceStack::new();
+    let x = Identifier::from("x");
+    nss.push(NameSpace::new());
+    assert!(nss.declare(&x, false, Value::Integer(1)).is_ok());
+    let address = nss.get_address(&x).unwrap();
+    nss.pop();
+    // un nouveau NameSpace à la même profondeur ne reprend pas l'adresse
+    nss.push(NameSpace::new());
+    assert!(nss.declare(&x, false, Value::Integer(2)).is_ok());
+    match nss.read_at(&address) {
+        Err(EvalError::NonAllocatedCell(None)) => (),
+        _ => panic!("Expected the popped cell to be unreachable"),
+    }
+}
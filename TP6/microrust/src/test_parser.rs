@@ -0,0 +1,69 @@
+use crate::{
+    parser::{Parse, ParseError},
+    parsing::{
+        expression::ExpressionKind,
+        instruction::{Instruction, InstructionKind},
+        span::Span,
+    },
+};
+
+#[test]
+fn test_parse_error_location() {
+    match Instruction::parse("let x = + 3") {
+        Err(ParseError::CannotParse{line: 1, column: 9, fragment, expected}) => {
+            assert_eq!(fragment, "+");
+            assert_eq!(expected, vec!["expr".to_string()]);
+        },
+        _ => panic!("Expected a parse error on `+`"),
+    }
+    match Instruction::parse("1 +") {
+        Err(ParseError::CannotParse{column: 4, fragment, ..}) => assert!(fragment.is_empty()),
+        _ => panic!("Expected a parse error at the end of input"),
+    }
+}
+
+#[test]
+fn test_spans() {
+    let instr = Instruction::parse("let x = 1 + y").unwrap();
+    assert_eq!(instr.span, Span::new(0, 13));
+    match instr.kind {
+        InstructionKind::Let{expr, ..} => match expr.kind {
+            ExpressionKind::BinOp(_, _, rhs) => assert_eq!(rhs.span.line_col("let x = 1 + y"), (1, 13)),
+            _ => panic!("Expected a binary operation"),
+        },
+        _ => panic!("Expected a let"),
+    }
+}
+
+#[test]
+fn test_incomplete_input() {
+    assert!(crate::is_incomplete("while true {"));
+    assert!(crate::is_incomplete("let x ="));
+    assert!(!crate::is_incomplete("while true {\n  1\n}"));
+    assert!(!crate::is_incomplete("1 + }"));
+}
+
+#[test]
+fn test_parse_program() {
+    let instrs = Vec::<Instruction>::parse("let x = 1;\nlet y = x\n{ x + y }").unwrap();
+    assert_eq!(instrs.len(), 3);
+    assert!(Vec::<Instruction>::parse("").unwrap().is_empty());
+    assert!(Vec::<Instruction>::parse("let x = (1").is_err());
+}
+
+#[test]
+fn test_lexical_details() {
+    let instrs = Vec::<Instruction>::parse("let\tx = 1; // commentaire\n/* bloc /* imbriqué */ */ let été = x\n+ 1;\nlet r#let = été").unwrap();
+    assert_eq!(instrs.len(), 3);
+    match &instrs[2].kind {
+        InstructionKind::Let{id, expr, ..} => {
+            assert_eq!(id.to_string(), "r#let");
+            assert!(matches!(&expr.kind, ExpressionKind::Identifier(id) if id.to_string() == "été"));
+        },
+        _ => panic!("Expected a let"),
+    }
+    assert!(Instruction::parse("let let = 1").is_err());
+    assert!(Instruction::parse("let letter = 1").is_ok());
+    assert!(Instruction::parse("1 /* non fermé").is_err());
+    assert!(crate::is_incomplete("1 /* non fermé"));
+}
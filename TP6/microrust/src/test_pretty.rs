@@ -0,0 +1,83 @@
+use crate::{
+    parser::Parse,
+    parsing::{expression::Expression, instruction::Instruction},
+    pretty::pretty_program,
+};
+
+fn pretty_expr(input: &str) -> String {
+    Expression::parse(input).unwrap().pretty()
+}
+
+// parse → format → parse redonne le même arbre, et formater à nouveau ne change rien
+fn assert_round_trip(input: &str) -> String {
+    let instrs = Vec::<Instruction>::parse(input).unwrap();
+    let formatted = pretty_program(&instrs, input);
+    let reparsed = Vec::<Instruction>::parse(&formatted).unwrap();
+    assert_eq!(instrs, reparsed, "{}", formatted);
+    assert_eq!(pretty_program(&reparsed, &formatted), formatted);
+    formatted
+}
+
+#[test]
+fn test_minimal_parentheses() {
+    assert_eq!(pretty_expr("((1 + 2)) * 3"), "(1 + 2) * 3");
+    assert_eq!(pretty_expr("1 + (2 * 3)"), "1 + 2 * 3");
+    assert_eq!(pretty_expr("(1 - 2) - 3"), "1 - 2 - 3");
+    assert_eq!(pretty_expr("1 - (2 - 3)"), "1 - (2 - 3)");
+    assert_eq!(pretty_expr("(x < 1) && (y == (2 % 3))"), "x < 1 && y == 2 % 3");
+    assert_eq!(pretty_expr("*(p)"), "*p");
+    assert_eq!(pretty_expr("((b) ? 1 : 2) + 3"), "((b) ? 1 : 2) + 3");
+}
+
+#[test]
+fn test_indented_blocks() {
+    let formatted = assert_round_trip("fn f(mut x:isize,p:&Ptr<bool>)->isize{while x>0{x=x-1};if *(*p){return x}else{}; x+1} let y=f(3,&Ptr::new());{free(y)}");
+    assert_eq!(formatted, "\
+fn f(mut x: isize, p: &Ptr<bool>) -> isize {
+    while x > 0 {
+        x = x - 1
+    };
+    if **p {
+        return x
+    } else {};
+    x + 1
+}
+
+let y = f(3, &Ptr::new());
+{
+    free(y)
+}
+");
+}
+
+#[test]
+fn test_round_trip() {
+    assert_round_trip("let mut a = (((1))); let b = (a == 1) ? (2 - (3 - a)) : ((a) ? 1 : 2) * 4; fn g() { return }; g()");
+    assert_round_trip("let r#while = 1; fn r#fn(r#if: isize) -> isize { r#if } r#fn(r#while) // fin");
+    assert_round_trip("let p = &(1 + 2); *p = (*p + 1) % 3 || (true && false) != false");
+}
+
+#[test]
+fn test_comments() {
+    let formatted = assert_round_trip("\
+// début
+let x = 1 + /* deux */ 2; // x
+fn f() { /* vide */ }
+while x < 0 { x = x - 1 /* décrément */ } // fin");
+    assert_eq!(formatted, "\
+// début
+/* deux */
+let x = 1 + 2;
+
+// x
+fn f() {
+    /* vide */
+}
+
+while x < 0 {
+    x = x - 1
+    /* décrément */
+}
+// fin
+");
+}
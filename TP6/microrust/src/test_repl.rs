@@ -0,0 +1,77 @@
+use crate::{
+    is_incomplete,
+    meta_command,
+    namespace::NameSpace,
+    namespacestack::NameSpaceStack,
+    parse_exec,
+    parsing::instruction::Instruction,
+    typecheck::TypeEnv,
+};
+
+struct Session {
+    source: String,
+    env: TypeEnv,
+    nss: NameSpaceStack,
+}
+
+impl Session {
+    fn new() -> Self {
+        let mut nss = NameSpaceStack::new();
+        nss.push(NameSpace::new());
+        Session { source: String::new(), env: TypeEnv::new(), nss }
+    }
+
+    fn exec(&mut self, input: &str) {
+        let offset = self.source.len();
+        self.source.push_str(input);
+        self.source.push('\n');
+        assert!(parse_exec(input, offset, &self.source, &mut self.env, &mut self.nss, Instruction::exec).is_ok());
+    }
+
+    fn command(&mut self, command: &str) -> String {
+        let mut out = vec![];
+        meta_command(&mut out, command, &mut self.source, &mut self.env, &mut self.nss);
+        String::from_utf8(out).unwrap()
+    }
+}
+
+#[test]
+fn test_env_and_heap() {
+    let mut session = Session::new();
+    session.exec("let mut x = 3");
+    session.exec("let p = Ptr::new()");
+    session.exec("*p = true");
+    assert_eq!(session.command(":env"), "[0] p : Ptr<bool> = @heap:0\n[0] mut x : isize = 3\n");
+    assert_eq!(session.command(":heap"), "@heap:0 = true (allocated by `Ptr::new()`)\n");
+    session.exec("free(p)");
+    assert_eq!(session.command(":heap"), "");
+}
+
+#[test]
+fn test_type_does_not_evaluate() {
+    let mut session = Session::new();
+    session.exec("let x = 3");
+    assert_eq!(session.command(":type x < 4"), "bool\n");
+    assert_eq!(session.command(":type (x == 3) ? Ptr::new() : Ptr::new()"), "Ptr<_>\n");
+    assert_eq!(session.command(":env"), "[0] x : isize = 3\n");
+}
+
+#[test]
+fn test_reset() {
+    let mut session = Session::new();
+    session.exec("let x = 3");
+    assert_eq!(session.command(":reset"), "");
+    assert_eq!(session.command(":env"), "");
+    session.exec("let x = true");
+    assert_eq!(session.command(":env"), "[0] x : bool = true\n");
+}
+
+#[test]
+fn test_incomplete_input() {
+    assert!(is_incomplete("fn f() {"));
+    assert!(is_incomplete("let a = 1 /* { /* */"));
+    assert!(is_incomplete("let a = 1 +"));
+    assert!(!is_incomplete("let a = 1 // {"));
+    assert!(!is_incomplete("let a = 1 /* { */"));
+    assert!(!is_incomplete("{ 1 } // /*\n}"));
+}
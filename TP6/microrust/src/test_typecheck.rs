@@ -0,0 +1,64 @@
+use crate::{
+    error::EvalError,
+    parser::Parse,
+    parsing::instruction::Instruction,
+    r#type::Type,
+    typecheck::TypeEnv,
+};
+
+fn typecheck(env: &mut TypeEnv, input: &str) -> Type {
+    let t = Instruction::parse(input).unwrap().typecheck(env);
+    env.resolve(&t)
+}
+
+#[test]
+fn test_infer_pointer() {
+    let mut env = TypeEnv::new();
+    typecheck(&mut env, "let p = Ptr::new()");
+    typecheck(&mut env, "*p = 3");
+    assert_eq!(typecheck(&mut env, "p"), Type::Pointer(Box::new(Type::Int)));
+    assert_eq!(typecheck(&mut env, "&*p"), Type::Ref(Box::new(Type::Int)));
+    assert!(env.take_errors().is_empty());
+}
+
+#[test]
+fn test_untaken_branches() {
+    let mut env = TypeEnv::new();
+    typecheck(&mut env, "if true {1} else {1 + false}");
+    typecheck(&mut env, "while false {free(1)}");
+    match env.take_errors().as_slice() {
+        [EvalError::TypeMismatch{expected: Type::Int, found: Some(Type::Bool), ..},
+         EvalError::TypeMismatch{expected: Type::Pointer(_), found: Some(Type::Int), ..}] => (),
+        errors => panic!("Unexpected errors {:?}", errors),
+    }
+}
+
+#[test]
+fn test_scopes() {
+    let mut env = TypeEnv::new();
+    typecheck(&mut env, "let x = 1");
+    assert_eq!(typecheck(&mut env, "{let x = true; x}"), Type::Bool);
+    assert_eq!(typecheck(&mut env, "x"), Type::Int);
+    typecheck(&mut env, "let x = 2");
+    typecheck(&mut env, "y");
+    let errors: Vec<EvalError> = env.take_errors().into_iter().map(EvalError::without_location).collect();
+    match errors.as_slice() {
+        [EvalError::AlreadyDefined(_), EvalError::Undefined(_)] => (),
+        errors => panic!("Unexpected errors {:?}", errors),
+    }
+}
+
+#[test]
+fn test_missing_values() {
+    let mut env = TypeEnv::new();
+    typecheck(&mut env, "if false { 1 } else { }");
+    typecheck(&mut env, "fn g() -> isize { while false {} }");
+    typecheck(&mut env, "return 3");
+    let errors: Vec<EvalError> = env.take_errors().into_iter().map(EvalError::without_location).collect();
+    match errors.as_slice() {
+        [EvalError::TypeMismatch{expected: Type::Int, found: Some(Type::Unit), ..},
+         EvalError::TypeMismatch{expected: Type::Int, found: Some(Type::Unit), ..},
+         EvalError::Return(_)] => (),
+        errors => panic!("Unexpected errors {:?}", errors),
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{
+    namespace::NameSpace,
+    namespacestack::NameSpaceStack,
+    parser::Parse,
+    parsing::instruction::Instruction,
+    Exec,
+};
+
+// valeurs, erreurs (avec leur position) et fuites produites par chaque instruction
+fn trace(program: &str, exec: Exec) -> Vec<String> {
+    let mut nss = NameSpaceStack::new();
+    nss.push(NameSpace::new());
+    let mut trace = vec![];
+    for instr in Vec::<Instruction>::parse(program).unwrap() {
+        match exec(&instr, &mut nss) {
+            Ok((id, value)) => trace.push(format!("{:?} = {}", id, value)),
+            Err(e) => trace.push(format!("{} at {:?}", e, e.span())),
+        }
+        nss.check_leaks(&crate::value::Value::Unit);
+        trace.extend(nss.take_leaks().iter().map(|e| e.to_string()));
+    }
+    trace
+}
+
+fn assert_same(program: &str) -> Vec<String> {
+    let expected = trace(program, Instruction::exec);
+    assert_eq!(trace(program, Instruction::exec_bytecode), expected, "{}", program);
+    expected
+}
+
+#[test]
+fn test_same_values() {
+    let trace = assert_same("
+        let mut i = 0; let mut s = 0;
+        while i < 10 { s = s + ((i % 3 == 0) ? 1 : 0) * i; i = i + 1 };
+        fn fact(n: isize) -> isize { if n <= 1 { return 1 } else { n * fact(n - 1) } };
+        fact(10);
+        let p = Ptr::new(); *p = &s; **p; free(p);
+        { let q = Ptr::new(); *q = 3; let r = q; *r }
+    ");
+    assert!(trace.contains(&"None = 3628800".to_string()));
+    assert!(trace.contains(&"None = 18".to_string()));
+}
+
+#[test]
+fn test_same_errors() {
+    let trace = assert_same("let x = 1; x = 2; y; 1 + true; 3 / (x - 1); let x = 0");
+    assert_eq!(trace[1], "Cell at `x` is not mutable. at Some(Span { start: 11, end: 12 })");
+    assert_same("fn f(a: isize) -> isize { let b = a; b + c }; f(1); f(); g(1); return 3");
+    assert_same("let x = 1; { let x = 2; let y = x; { let x = 3; x + y } }; { let z = 1; z = 2 }; { let mut z = 1; z = z + x; z }; u = 3; u");
+    assert_same("fn f(a: isize, a: isize) -> isize { a }; f(1, 2); { let w = 1; let w = 2; w }; w");
+    assert_same("let p = Ptr::new(); let q = p; *p; free(q); *q; free(q); free(&p)");
+    assert_same("let p = Ptr::new(); let r = &p; free(*r); **r; *p");
+    assert_same("let mut i = 0; while i < 3 { let p = Ptr::new(); i = i + 1 }; { Ptr::new() }");
+    assert_same("fn f() -> isize { { let p = Ptr::new(); return 1 } }; f(); &(1 + 2)");
+    assert_same("fn h() -> Ptr<isize> { Ptr::new() }; free(h()); free(1)");
+    assert_same("fn g() -> isize { let q = Ptr::new(); free(q); 1 }; fn f(p: Ptr<isize>, x: isize) -> isize { free(p); x }; f(Ptr::new(), g())");
+}
+
+#[test]
+fn test_same_moves() {
+    let trace = assert_same("
+        let p = Ptr::new(); *p = Ptr::new(); let q = *p; *p;
+        let r = &q; let s = *r; free(q);
+        let pp = Ptr::new(); *pp = Ptr::new(); **pp = Ptr::new(); let t = **pp; free(t); free(*pp); free(pp); free(p)
+    ");
+    assert!(trace.iter().any(|line| line.starts_with("cannot move `*r`")));
+    assert_same("9223372036854775807 * 2; let m = 0 - 9223372036854775807 - 1; m / (0 - 1); m % (0 - 1); m - 1");
+}
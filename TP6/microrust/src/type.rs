@@ -1,8 +1,14 @@
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Type {
     Int,
     Bool,
     Unit,
+    Pointer(Box<Type>),
+    Ref(Box<Type>),
+    // variable d'inférence du typeur, pas encore résolue
+    Var(usize),
+    // type inconnu à l'exécution (contenu d'un pointeur)
+    Unknown,
 }
 
 use std::fmt::{self, Display};
@@ -14,7 +20,9 @@ impl Display for Type {
             Int => write!(f, "isize"),
             Bool => write!(f, "bool"),
             Unit => write!(f, "unit"),
+            Pointer(t) => write!(f, "Ptr<{}>", t),
+            Ref(t) => write!(f, "&{}", t),
+            Var(_) | Unknown => write!(f, "_"),
         }
     }
 }
-
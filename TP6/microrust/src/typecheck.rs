@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+
+use crate::identifier::Identifier;
+use crate::parsing::expression::{Expression, ExpressionKind};
+use crate::parsing::instruction::{Instruction, InstructionKind};
+use crate::parsing::span::Span;
+use crate::parsing::parsedvalue::ParsedValue;
+use crate::parsing::binop::Binop;
+use crate::error::EvalError;
+use crate::r#type::Type;
+use crate::value::Value;
+use ExpressionKind::*;
+
+// environnement de typage : une pile de portées parallèle à la NameSpaceStack,
+// la signature des fonctions, le type de retour de la fonction en cours de
+// vérification, la substitution des variables d'inférence, et les erreurs rencontrées
+#[derive(Debug, Clone)]
+pub struct TypeEnv {
+    scopes: Vec<HashMap<Identifier, Type>>,
+    functions: HashMap<Identifier, (Vec<Type>, Type)>,
+    returns: Vec<Type>,
+    subst: Vec<Option<Type>>,
+    errors: Vec<EvalError>,
+}
+
+impl TypeEnv {
+    pub fn new() -> Self {
+        TypeEnv { scopes: vec![HashMap::new()], functions: HashMap::new(), returns: vec![], subst: vec![], errors: vec![] }
+    }
+
+    fn push(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn pop(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, id: &Identifier, t: Type, span: Span) {
+        let scope = self.scopes.last_mut().unwrap();
+        if scope.contains_key(id) {
+            self.errors.push(EvalError::AlreadyDefined(id.clone()).located(span));
+        } else {
+            scope.insert(id.clone(), t);
+        }
+    }
+
+    fn find(&self, id: &Identifier) -> Option<Type> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(id).cloned())
+    }
+
+    fn fresh(&mut self) -> Type {
+        self.subst.push(None);
+        Type::Var(self.subst.len() - 1)
+    }
+
+    // remplace récursivement les variables d'inférence déjà résolues
+    pub fn resolve(&self, t: &Type) -> Type {
+        match t {
+            Type::Var(v) => match &self.subst[*v] {
+                Some(t) => self.resolve(t),
+                None => t.clone(),
+            },
+            Type::Pointer(t) => Type::Pointer(Box::new(self.resolve(t))),
+            Type::Ref(t) => Type::Ref(Box::new(self.resolve(t))),
+            _ => t.clone(),
+        }
+    }
+
+    fn occurs(&self, v: usize, t: &Type) -> bool {
+        match self.resolve(t) {
+            Type::Var(w) => v == w,
+            Type::Pointer(t) | Type::Ref(t) => self.occurs(v, &t),
+            _ => false,
+        }
+    }
+
+    fn unify(&mut self, t1: &Type, t2: &Type) -> bool {
+        match (self.resolve(t1), self.resolve(t2)) {
+            (Type::Var(v), Type::Var(w)) if v == w => true,
+            (Type::Var(v), t) | (t, Type::Var(v)) => {
+                if self.occurs(v, &t) {
+                    return false;
+                }
+                self.subst[v] = Some(t);
+                true
+            },
+            (Type::Unknown, _) | (_, Type::Unknown) => true,
+            (Type::Pointer(t1), Type::Pointer(t2)) | (Type::Ref(t1), Type::Ref(t2)) => self.unify(&t1, &t2),
+            (t1, t2) => t1 == t2,
+        }
+    }
+
+    // `found`, le type de `expression`, doit être compatible avec `expected`
+    fn expect(&mut self, expression: &Expression, expected: &Type, found: &Type) {
+        if !self.unify(expected, found) {
+            self.errors.push(EvalError::TypeMismatch {
+                expression: expression.clone(),
+                expected: self.resolve(expected),
+                found: Some(self.resolve(found)),
+            });
+        }
+    }
+
+    // comme `expect`, pour la valeur d'une instruction : sans expression
+    // finale (bloc vide, `while`...), l'instruction vaut `()`
+    fn expect_instruction(&mut self, instr: &Instruction, expected: &Type, found: &Type) {
+        match instr.tail_expression() {
+            Some(e) => self.expect(e, expected, found),
+            None => {
+                let unit = Expression { kind: Const(ParsedValue::Unit), span: instr.span };
+                self.expect(&unit, expected, found);
+            },
+        }
+    }
+
+    // type inféré pour la variable `id`, telle que vue depuis la portée courante
+    pub fn type_of(&self, id: &Identifier) -> Option<Type> {
+        self.find(id).map(|t| self.resolve(&t))
+    }
+
+    pub fn take_errors(&mut self) -> Vec<EvalError> {
+        std::mem::take(&mut self.errors)
+    }
+}
+
+
+impl Expression {
+    pub fn typecheck(&self, env: &mut TypeEnv) -> Type {
+        match &self.kind {
+            Const(ParsedValue::Integer(_)) => Type::Int,
+            Const(ParsedValue::Boolean(_)) => Type::Bool,
+            Const(ParsedValue::Unit) => Type::Unit,
+            Identifier(id) => env.find(id).unwrap_or_else(|| {
+                env.errors.push(EvalError::Undefined(id.clone()).located(self.span));
+                env.fresh()
+            }),
+            BinOp(e1, op, e2) => {
+                let t1 = e1.typecheck(env);
+                let t2 = e2.typecheck(env);
+                let (operands, result) = match op {
+                    Binop::Add | Binop::Sub | Binop::Mul | Binop::Div | Binop::Mod => (Type::Int, Type::Int),
+                    Binop::Leq | Binop::Geq | Binop::Lt | Binop::Gt => (Type::Int, Type::Bool),
+                    Binop::And | Binop::Or => (Type::Bool, Type::Bool),
+                    Binop::Eq | Binop::Neq => (t1.clone(), Type::Bool),
+                };
+                env.expect(e1, &operands, &t1);
+                env.expect(e2, &operands, &t2);
+                result
+            },
+            Conditional{cond, cond_true, cond_false} => {
+                let t = cond.typecheck(env);
+                env.expect(cond, &Type::Bool, &t);
+                let t1 = cond_true.typecheck(env);
+                let t2 = cond_false.typecheck(env);
+                env.expect(cond_false, &t1, &t2);
+                t1
+            },
+            NewPtr => Type::Pointer(Box::new(env.fresh())),
+            Deref(e) => {
+                let t = e.typecheck(env);
+                match env.resolve(&t) {
+                    Type::Pointer(inner) | Type::Ref(inner) => *inner,
+                    _ => {
+                        let inner = env.fresh();
+                        env.expect(e, &Type::Pointer(Box::new(inner.clone())), &t);
+                        inner
+                    },
+                }
+            },
+            AmpersAnd(e) => Type::Ref(Box::new(e.typecheck(env))),
+            Call(id, args) => {
+                let types: Vec<Type> = args.iter().map(|arg| arg.typecheck(env)).collect();
+                let (params, ret) = match env.functions.get(id) {
+                    Some(signature) => signature.clone(),
+                    None => {
+                        env.errors.push(EvalError::Undefined(id.clone()).located(self.span));
+                        return env.fresh();
+                    },
+                };
+                if params.len() != args.len() {
+                    env.errors.push(EvalError::WrongArity{id: id.clone(), expected: params.len(), found: args.len()}.located(self.span));
+                }
+                for ((arg, t), param) in args.iter().zip(&types).zip(&params) {
+                    env.expect(arg, param, t);
+                }
+                ret
+            },
+        }
+    }
+}
+
+
+impl Instruction {
+    // dernière expression évaluée par l'instruction, celle qui donne sa valeur
+    fn tail_expression(&self) -> Option<&Expression> {
+        match &self.kind {
+            InstructionKind::Expr(e) | InstructionKind::Let{expr: e, ..} => Some(e),
+            InstructionKind::Block(instrs) => instrs.last()?.tail_expression(),
+            InstructionKind::IfElse{cond_false, ..} => cond_false.tail_expression(),
+            _ => None,
+        }
+    }
+
+    // les deux branches d'un `if` et le corps d'un `while` sont vérifiés,
+    // qu'ils soient exécutés ou non
+    pub fn typecheck(&self, env: &mut TypeEnv) -> Type {
+        match &self.kind {
+            InstructionKind::Expr(e) => e.typecheck(env),
+            InstructionKind::Let{id, mutable: _, expr} => {
+                let t = expr.typecheck(env);
+                env.declare(id, t.clone(), self.span);
+                t
+            },
+            InstructionKind::Block(instrs) => {
+                env.push();
+                let mut t = Type::Unit;
+                for instr in instrs {
+                    t = instr.typecheck(env);
+                }
+                env.pop();
+                t
+            },
+            InstructionKind::IfElse{cond, cond_true, cond_false} => {
+                let t = cond.typecheck(env);
+                env.expect(cond, &Type::Bool, &t);
+                let t1 = cond_true.typecheck(env);
+                let t2 = cond_false.typecheck(env);
+                env.expect_instruction(cond_false, &t1, &t2);
+                t1
+            },
+            InstructionKind::While(cond, body) => {
+                let t = cond.typecheck(env);
+                env.expect(cond, &Type::Bool, &t);
+                body.typecheck(env);
+                Type::Unit
+            },
+            InstructionKind::WriteAt(e1, e2) => {
+                let t1 = e1.typecheck(env);
+                let t2 = e2.typecheck(env);
+                env.expect(e2, &t1, &t2);
+                Type::Unit
+            },
+            InstructionKind::Free(e) => {
+                let t = e.typecheck(env);
+                let inner = env.fresh();
+                env.expect(e, &Type::Pointer(Box::new(inner)), &t);
+                Type::Unit
+            },
+            InstructionKind::FnDef(function) => {
+                if env.functions.contains_key(&function.id) {
+                    env.errors.push(EvalError::AlreadyDefined(function.id.clone()).located(self.span));
+                    return Type::Unit;
+                }
+                let params = function.params.iter().map(|param| param.ty.clone()).collect();
+                env.functions.insert(function.id.clone(), (params, function.ret.clone()));
+                // le corps est vérifié sans les variables de l'appelant
+                let mut scope = HashMap::new();
+                for param in &function.params {
+                    if scope.insert(param.id.clone(), param.ty.clone()).is_some() {
+                        env.errors.push(EvalError::AlreadyDefined(param.id.clone()).located(self.span));
+                    }
+                }
+                let scopes = std::mem::replace(&mut env.scopes, vec![scope]);
+                env.returns.push(function.ret.clone());
+                let t = function.body.typecheck(env);
+                if function.ret != Type::Unit {
+                    env.expect_instruction(&function.body, &function.ret, &t);
+                }
+                env.returns.pop();
+                env.scopes = scopes;
+                Type::Unit
+            },
+            // `return` ne produit pas de valeur : son type s'unifie avec tout
+            InstructionKind::Return(e) => {
+                let t = e.typecheck(env);
+                match env.returns.last().cloned() {
+                    Some(ret) => env.expect(e, &ret, &t),
+                    // `located` laisse passer `Return`, signal de contrôle à l'évaluation
+                    None => env.errors.push(EvalError::Located(self.span, Box::new(EvalError::Return(Value::Unit)))),
+                }
+                env.fresh()
+            },
+        }
+    }
+}
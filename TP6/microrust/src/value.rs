@@ -1,10 +1,13 @@
 use std::fmt;
 
+use crate::memory::Address;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     Integer(isize),
     Boolean(bool),
     Unit,
+    Pointer(Address),
 }
 
 impl fmt::Display for Value {
@@ -14,6 +17,7 @@ impl fmt::Display for Value {
             Value::Integer(i) => write!(f, "{}", i),
             Value::Boolean(b) => write!(f, "{}", b),
             Value::Unit => write!(f, "()"),
+            Value::Pointer(a) => write!(f, "{}", a),
         }
     }
 }
@@ -36,6 +40,8 @@ impl From<&Value> for Type {
             Value::Integer(_) => Type::Int,
             Value::Boolean(_) => Type::Bool,
             Value::Unit => Type::Unit,
+            Value::Pointer(Address::HeapAddress(_)) => Type::Pointer(Box::new(Type::Unknown)),
+            Value::Pointer(Address::StackAddress(_, _)) => Type::Ref(Box::new(Type::Unknown)),
         }
     }
 }
@@ -53,4 +59,15 @@ impl Value {
             _ => Err(Type::from(self)),
         }
     }
+    // seuls les pointeurs vers le tas possèdent leur cellule : ils sont
+    // déplacés au lieu d'être copiés
+    pub fn is_owned(&self) -> bool {
+        matches!(self, Value::Pointer(Address::HeapAddress(_)))
+    }
+    pub fn to_address(&self) -> Result<Address, Type> {
+        match self {
+            Value::Pointer(a) => Ok(a.clone()),
+            _ => Err(Type::from(self)),
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,246 @@
+use std::rc::Rc;
+
+use crate::bytecode::{Chunk, Op};
+use crate::error::EvalError;
+use crate::eval::arithmetic;
+use crate::identifier::Identifier;
+use crate::memory::Address;
+use crate::namespace::NameSpace;
+use crate::namespacestack::NameSpaceStack;
+use crate::parsing::binop::Binop;
+use crate::parsing::function::Function;
+use crate::parsing::instruction::{Instruction, InstructionKind};
+use crate::r#type::Type;
+use crate::value::Value;
+
+// machine à pile : les variables et le tas restent ceux de la NameSpaceStack,
+// pour que blocs, appels et fuites se comportent comme dans l'interpréteur
+struct Vm<'a> {
+    nss: &'a mut NameSpaceStack,
+    stack: Vec<Value>,
+    // fonctions dont les arguments sont en cours d'évaluation
+    calls: Vec<Rc<Function>>,
+    // `stack[..held]` est retenu dans la NameSpaceStack pendant les appels
+    held: usize,
+}
+
+impl Vm<'_> {
+    fn pop(&mut self) -> Value {
+        self.stack.pop().unwrap()
+    }
+
+    fn top(&self) -> &Value {
+        self.stack.last().unwrap()
+    }
+
+    // un corps de fonction n'est compilé qu'une fois par session
+    fn chunk(&mut self, function: &Function) -> Rc<Chunk> {
+        self.nss.chunks().entry(function.id.clone())
+            .or_insert_with(|| Rc::new(function.compile()))
+            .clone()
+    }
+
+    // en cas d'erreur, les blocs ouverts par le code sont refermés comme le
+    // fait `Instruction::exec`, en gardant la valeur d'un `return`
+    fn run(&mut self, chunk: &Chunk) -> Result<Value, EvalError> {
+        let (stack, calls) = (self.stack.len(), self.calls.len());
+        let mut blocks = 0;
+        let mut pc = 0;
+        while pc < chunk.ops.len() {
+            match self.step(chunk, pc, &mut blocks) {
+                Ok(next) => pc = next,
+                Err(err) => {
+                    let err = err.located(chunk.spans[pc]);
+                    for _ in 0..blocks {
+                        match &err {
+                            EvalError::Return(value) => self.nss.pop_keeping(value),
+                            _ => self.nss.pop(),
+                        };
+                    }
+                    self.stack.truncate(stack);
+                    self.calls.truncate(calls);
+                    return Err(err);
+                },
+            }
+        }
+        Ok(self.pop())
+    }
+
+    // exécute `chunk.ops[pc]` et renvoie l'indice de l'op suivante
+    fn step(&mut self, chunk: &Chunk, pc: usize, blocks: &mut usize) -> Result<usize, EvalError> {
+        match &chunk.ops[pc] {
+            Op::Const(v) => self.stack.push(v.clone()),
+            Op::Load{depth, slot, expr} => {
+                let value = self.nss.find_slot(*depth, *slot).map_err(|err| err.with_expression_info(chunk.exprs[*expr].clone()))?;
+                self.stack.push(value);
+            },
+            Op::CheckInt(e) => self.check(chunk, *e, Type::Int, Value::to_int)?,
+            Op::CheckBool(e) => self.check(chunk, *e, Type::Bool, Value::to_bool)?,
+            Op::CheckAddress(e) => self.check(chunk, *e, Type::Pointer(Box::new(Type::Unknown)), Value::to_address)?,
+            Op::Binary{op, expr, rhs} => {
+                let v2 = self.pop();
+                let v1 = self.pop();
+                let value = match (v1, op, v2) {
+                    (v1, Binop::Eq, v2) => Value::Boolean(v1 == v2),
+                    (v1, Binop::Neq, v2) => Value::Boolean(v1 != v2),
+                    (Value::Boolean(b1), Binop::And, Value::Boolean(b2)) => Value::Boolean(b1 && b2),
+                    (Value::Boolean(b1), Binop::Or, Value::Boolean(b2)) => Value::Boolean(b1 || b2),
+                    (Value::Integer(_), Binop::Div | Binop::Mod, Value::Integer(0)) =>
+                        return Err(EvalError::DivisionByZero(chunk.exprs[*rhs].clone())),
+                    (Value::Integer(i1), op, Value::Integer(i2)) => match op {
+                        Binop::Add | Binop::Sub | Binop::Mul | Binop::Div | Binop::Mod => arithmetic(*op, i1, i2)
+                            .map(Value::Integer)
+                            .ok_or_else(|| EvalError::Overflow(chunk.exprs[*expr].clone()))?,
+                        Binop::Leq => Value::Boolean(i1 <= i2),
+                        Binop::Geq => Value::Boolean(i1 >= i2),
+                        Binop::Lt => Value::Boolean(i1 < i2),
+                        Binop::Gt => Value::Boolean(i1 > i2),
+                        _ => unreachable!(),
+                    },
+                    _ => unreachable!("operands are checked before {:?}", op),
+                };
+                self.stack.push(value);
+            },
+            Op::Jump(to) => return Ok(*to),
+            Op::JumpIfFalse(to) => if self.pop() == Value::Boolean(false) {
+                return Ok(*to);
+            },
+            Op::NewPtr(e) => {
+                let address = self.nss.malloc(chunk.exprs[*e].clone());
+                self.stack.push(Value::Pointer(address));
+            },
+            Op::Read(e) => {
+                let address = self.pop().to_address().unwrap();
+                let value = self.nss.read_at(&address).map_err(|err| err.with_expression_info(chunk.exprs[*e].clone()))?;
+                self.stack.push(value);
+            },
+            Op::AddressOf(id) => {
+                let address = self.nss.get_address(id)?;
+                self.stack.push(Value::Pointer(address));
+            },
+            Op::Fail(err) => return Err(err.clone()),
+            Op::Move{depth, slot, expr} => {
+                let value = self.nss.find_slot(*depth, *slot).map_err(|err| err.with_expression_info(chunk.exprs[*expr].clone()))?;
+                if value.is_owned() {
+                    self.nss.move_slot(*depth, *slot).map_err(|err| err.with_expression_info(chunk.exprs[*expr].clone()))?;
+                }
+                self.stack.push(value);
+            },
+            Op::ReadOwner(e) => {
+                let (owner, _, value) = self.read_owned(chunk, *e)?;
+                self.stack.push(Value::Boolean(owner));
+                self.stack.push(value);
+            },
+            Op::MoveAt(e) => {
+                let (owner, address, value) = self.read_owned(chunk, *e)?;
+                if value.is_owned() {
+                    if !owner {
+                        return Err(EvalError::CannotMoveOwnedValue(Some(chunk.exprs[*e].clone())));
+                    }
+                    self.nss.move_out(&address).map_err(|err| err.with_expression_info(chunk.exprs[*e].clone()))?;
+                }
+                self.stack.push(value);
+            },
+            Op::Store(e) => {
+                let address = self.pop().to_address().unwrap();
+                let value = self.pop();
+                self.nss.write_at(&address, value).map_err(|err| err.with_expression_info(chunk.exprs[*e].clone()))?;
+                self.stack.push(Value::Unit);
+            },
+            Op::Assign{depth, slot, expr} => {
+                let value = self.pop();
+                self.nss.set_slot(*depth, *slot, value).map_err(|err| err.with_expression_info(chunk.exprs[*expr].clone()))?;
+                self.stack.push(Value::Unit);
+            },
+            Op::Free(e) => {
+                let address = self.pop().to_address().unwrap();
+                self.nss.free(&address).map_err(|err| err.with_expression_info(chunk.exprs[*e].clone()))?;
+                self.stack.push(Value::Unit);
+            },
+            Op::Pop => { self.pop(); },
+            Op::Declare(id, mutable) => {
+                let value = self.top().clone();
+                self.nss.declare(id, *mutable, value)?;
+            },
+            Op::EnterBlock => {
+                self.nss.push(NameSpace::new());
+                *blocks += 1;
+            },
+            Op::ExitBlock => {
+                *blocks -= 1;
+                self.nss.pop_keeping(self.stack.last().unwrap());
+            },
+            Op::PrepareCall(id, argc) => {
+                let function = self.nss.function(id)?;
+                if *argc != function.params.len() {
+                    return Err(EvalError::WrongArity{id: id.clone(), expected: function.params.len(), found: *argc});
+                }
+                self.calls.push(function);
+            },
+            Op::Call(argc) => {
+                let function = self.calls.pop().unwrap();
+                let values = self.stack.split_off(self.stack.len() - argc);
+                let body = self.chunk(&function);
+                // les valeurs en attente sur la pile restent atteignables
+                // pendant l'appel
+                let held = self.held;
+                for value in &self.stack[held..] {
+                    self.nss.hold(value.clone());
+                }
+                self.held = self.stack.len();
+                // l'appelé ne voit que ses paramètres
+                self.nss.push_frame(NameSpace::new());
+                let result = function.params.iter().zip(values)
+                    .try_for_each(|(param, value)| self.nss.declare(&param.id, param.mutable, value))
+                    .and_then(|_| match self.run(&body) {
+                        Ok(value) | Err(EvalError::Return(value)) => Ok(value),
+                        Err(err) => Err(err),
+                    })
+                    .map(|value| if function.ret == Type::Unit { Value::Unit } else { value });
+                self.nss.pop_frame(result.as_ref().unwrap_or(&Value::Unit));
+                self.nss.release(self.held - held);
+                self.held = held;
+                self.stack.push(result?);
+            },
+            Op::DefineFunction(function) => {
+                self.nss.define_function(function.clone())?;
+                self.stack.push(Value::Unit);
+            },
+            Op::Return => return Err(EvalError::Return(self.pop())),
+        }
+        Ok(pc + 1)
+    }
+
+    // dépile (propriétaire de `p`, `p`) pour lire `chunk.exprs[e]`, qui vaut
+    // `*p` : `*p` est propriétaire si `p` l'est et possède sa cellule
+    fn read_owned(&mut self, chunk: &Chunk, e: usize) -> Result<(bool, Address, Value), EvalError> {
+        let address = self.pop().to_address().unwrap();
+        let owner = self.pop() == Value::Boolean(true) && matches!(address, Address::HeapAddress(_));
+        let value = self.nss.read_at(&address).map_err(|err| err.with_expression_info(chunk.exprs[e].clone()))?;
+        Ok((owner, address, value))
+    }
+
+    // le sommet de pile, valeur de `chunk.exprs[e]`, doit être du type `expected`
+    fn check<T>(&self, chunk: &Chunk, e: usize, expected: Type, cast: fn(&Value) -> Result<T, Type>) -> Result<(), EvalError> {
+        match cast(self.top()) {
+            Ok(_) => Ok(()),
+            Err(found) => Err(EvalError::TypeMismatch{expression: chunk.exprs[e].clone(), expected, found: Some(found)}),
+        }
+    }
+}
+
+
+impl Instruction {
+    // même résultat que `exec`, en passant par le bytecode
+    pub fn exec_bytecode(&self, nss: &mut NameSpaceStack) -> Result<(Option<Identifier>, Value), EvalError> {
+        let chunk = self.compile(nss.scopes());
+        let mut vm = Vm { nss, stack: vec![], calls: vec![], held: 0 };
+        let value = vm.run(&chunk).map_err(|err| err.located(self.span))?;
+        let id = match &self.kind {
+            InstructionKind::Let{id, ..} => Some(id.clone()),
+            InstructionKind::FnDef(function) => Some(function.id.clone()),
+            _ => None,
+        };
+        Ok((id, value))
+    }
+}